@@ -0,0 +1,225 @@
+use crate::regions::parse_groups;
+use std::fmt;
+
+/// A killer cage: its digits must not repeat and must add up to `sum`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cage {
+    pub cells: Vec<(usize, usize)>,
+    pub sum: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CageError {
+    Empty,
+    CellOutOfRange {
+        row: usize,
+        col: usize,
+    },
+    DuplicateCell {
+        row: usize,
+        col: usize,
+    },
+    /// A cell is already in another cage.
+    Overlap {
+        row: usize,
+        col: usize,
+    },
+    /// No set of distinct digits fills the cage with its sum.
+    ImpossibleSum {
+        sum: i32,
+        cells: usize,
+    },
+    /// The cage layout is not a square grid.
+    NotSquare,
+    WrongSumCount {
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for CageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CageError::Empty => write!(f, "cage has no cells"),
+            CageError::CellOutOfRange { row, col } => {
+                write!(f, "r{}c{} is outside the board", row + 1, col + 1)
+            }
+            CageError::DuplicateCell { row, col } => {
+                write!(f, "r{}c{} appears twice in one cage", row + 1, col + 1)
+            }
+            CageError::Overlap { row, col } => {
+                write!(f, "r{}c{} is in more than one cage", row + 1, col + 1)
+            }
+            CageError::ImpossibleSum { sum, cells } => {
+                write!(f, "no {} distinct digits add up to {}", cells, sum)
+            }
+            CageError::NotSquare => write!(f, "cage layout is not a square grid"),
+            CageError::WrongSumCount { expected, found } => {
+                write!(f, "expected {} cage sums but found {}", expected, found)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CageError {}
+
+impl Cage {
+    pub fn new(cells: Vec<(usize, usize)>, sum: i32) -> Cage {
+        Cage { cells, sum }
+    }
+
+    /// Cages from a layout like `ExtraUnit::parse`, one label per cage, and
+    /// their sums in order of each label's first appearance. Cells marked
+    /// `.` are in no cage.
+    pub fn parse(layout: &str, sums: &[i32]) -> Result<Vec<Cage>, CageError> {
+        let (_, groups) = parse_groups(layout).ok_or(CageError::NotSquare)?;
+        if groups.len() != sums.len() {
+            return Err(CageError::WrongSumCount {
+                expected: groups.len(),
+                found: sums.len(),
+            });
+        }
+        Ok(groups
+            .into_iter()
+            .zip(sums)
+            .map(|(cells, sum)| Cage::new(cells, *sum))
+            .collect())
+    }
+
+    /// The digit sets that can fill the cage on a board of side `size`, each
+    /// in ascending order.
+    pub fn combinations(&self, size: usize) -> Vec<Vec<i32>> {
+        combinations(self.sum, self.cells.len(), size)
+    }
+
+    /// Checks that the cage fits a board of side `size` and can be filled.
+    pub fn check(&self, size: usize) -> Result<(), CageError> {
+        if self.cells.is_empty() {
+            return Err(CageError::Empty);
+        }
+        for (index, (row, col)) in self.cells.iter().enumerate() {
+            if *row >= size || *col >= size {
+                return Err(CageError::CellOutOfRange {
+                    row: *row,
+                    col: *col,
+                });
+            }
+            if self.cells[..index].contains(&(*row, *col)) {
+                return Err(CageError::DuplicateCell {
+                    row: *row,
+                    col: *col,
+                });
+            }
+        }
+        if self.combinations(size).is_empty() {
+            return Err(CageError::ImpossibleSum {
+                sum: self.sum,
+                cells: self.cells.len(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Every set of `count` distinct digits from 1 to `size` adding up to `sum`,
+/// each in ascending order, e.g. `[1, 2, 4]` for three digits summing to 7.
+pub fn combinations(sum: i32, count: usize, size: usize) -> Vec<Vec<i32>> {
+    let mut found = Vec::new();
+    extend_combination(&mut Vec::new(), 1, sum, count, size as i32, &mut found);
+    found
+}
+
+fn extend_combination(
+    digits: &mut Vec<i32>,
+    next: i32,
+    remaining: i32,
+    count: usize,
+    size: i32,
+    found: &mut Vec<Vec<i32>>,
+) {
+    if digits.len() == count {
+        if remaining == 0 {
+            found.push(digits.clone());
+        }
+        return;
+    }
+    for digit in next..=size.min(remaining) {
+        digits.push(digit);
+        extend_combination(digits, digit + 1, remaining - digit, count, size, found);
+        digits.pop();
+    }
+}
+
+#[test]
+fn lists_cage_combinations() {
+    assert_eq!(combinations(7, 3, 9), vec![vec![1, 2, 4]]);
+    assert_eq!(
+        combinations(10, 3, 9),
+        vec![vec![1, 2, 7], vec![1, 3, 6], vec![1, 4, 5], vec![2, 3, 5]]
+    );
+    assert_eq!(combinations(45, 9, 9).len(), 1);
+    assert_eq!(combinations(17, 2, 9), vec![vec![8, 9]]);
+    assert!(combinations(18, 2, 9).is_empty());
+
+    let cage = Cage::new(vec![(0, 0), (0, 1)], 3);
+    assert_eq!(cage.combinations(9), vec![vec![1, 2]]);
+    assert_eq!(cage.check(9), Ok(()));
+    assert_eq!(
+        Cage::new(vec![(0, 0), (0, 0)], 3).check(9),
+        Err(CageError::DuplicateCell { row: 0, col: 0 })
+    );
+    assert_eq!(
+        Cage::new(vec![(0, 0), (0, 1)], 2).check(9),
+        Err(CageError::ImpossibleSum { sum: 2, cells: 2 })
+    );
+
+    let cages = Cage::parse("aab. cdb. cdee ....", &[3, 10, 5, 7, 9]).unwrap();
+    assert_eq!(cages[1], Cage::new(vec![(0, 2), (1, 2)], 10));
+    assert_eq!(cages[4].cells, vec![(2, 2), (2, 3)]);
+    assert_eq!(
+        Cage::parse("aabb", &[3]),
+        Err(CageError::WrongSumCount {
+            expected: 2,
+            found: 1
+        })
+    );
+}
+
+#[test]
+fn solves_killer() {
+    use crate::explain::explain_step;
+    use crate::logic::{self, Technique, Unit};
+    use crate::sudoku::SudokuBoard;
+
+    let layout =
+        "aabbcddee afbbcgeeh ifffggjjh iiklmmjhh nnklmmoop qqrrsttup vvwwstxup yzzAstxuu yyyAsBBBB";
+    let sums = [
+        9, 24, 10, 8, 23, 22, 16, 19, 11, 10, 8, 12, 23, 6, 16, 15, 14, 10, 16, 16, 26, 14, 9, 11,
+        25, 4, 12, 16,
+    ];
+    let mut board = SudokuBoard::new();
+    board.puzzle[0][2] = 4;
+    SudokuBoard::add_cages(&mut board, Cage::parse(layout, &sums).unwrap()).unwrap();
+    // r1c1 shares a cage of 9 with r1c2 and r2c1.
+    assert_eq!(board.possible_solutions[0][0], vec![1, 2, 3, 5, 6]);
+
+    let path = logic::solve_path(&board);
+    assert!(path.solved());
+    let find = |technique: Technique| {
+        path.steps
+            .iter()
+            .find(|step| step.technique == technique)
+            .unwrap()
+    };
+    let rule_of_45 = find(Technique::RuleOf45);
+    assert_eq!(rule_of_45.units, vec![Unit::Box(6)]);
+    assert_eq!(rule_of_45.placement, Some((6, 2, 2)));
+    assert!(explain_step(rule_of_45).contains("the cage sums leave"));
+    let combination = find(Technique::CageCombination);
+    assert!(matches!(combination.units[..], [Unit::Cage(_)]));
+    assert_eq!(combination.eliminations, vec![(3, 2, 6), (4, 2, 6)]);
+    assert_eq!(
+        SudokuBoard::add_cages(&mut board, vec![Cage::new(vec![(0, 0), (0, 1)], 3)]),
+        Err(CageError::Overlap { row: 0, col: 0 })
+    );
+}
@@ -0,0 +1,140 @@
+use crate::hash::fnv1a;
+use crate::sudoku::{Grid, SudokuBoard};
+use crate::transform::{permute_cols_in_stack, permute_stacks, transpose};
+
+const ORDERS: [[usize; 3]; 6] = [
+    [0, 1, 2],
+    [0, 2, 1],
+    [1, 0, 2],
+    [1, 2, 0],
+    [2, 0, 1],
+    [2, 1, 0],
+];
+
+/// Empty slots in the best grid found so far compare greater than any digit.
+const UNSET: i32 = 10;
+
+/// The minimal lexicographic form of `puzzle` over every transformation in
+/// `transform`: transposition, band/stack and row/column permutations, and
+/// digit relabelling. Empty cells read as 0, so the form puts as many empty
+/// cells first as possible. Two puzzles are equivalent exactly when their
+/// forms are equal.
+///
+/// For each of the 2 x 1296 column arrangements, rows are chosen one at a
+/// time and a branch is dropped as soon as its prefix exceeds the best form
+/// found so far. Digits are relabelled in order of first appearance, which is
+/// the smallest labelling for a fixed arrangement.
+///
+/// Only classic 9x9 puzzles have a form; other sizes give `None`.
+pub fn minlex(puzzle: &Grid) -> Option<Grid> {
+    if puzzle.len() != 9 || puzzle.iter().any(|row| row.len() != 9) {
+        return None;
+    }
+    let mut best = vec![vec![UNSET; 9]; 9];
+    for grid in [puzzle.clone(), transpose(puzzle)] {
+        for stacks in ORDERS {
+            let stacked = permute_stacks(&grid, &stacks);
+            for first in ORDERS {
+                let first_applied = permute_cols_in_stack(&stacked, 0, &first);
+                for second in ORDERS {
+                    let second_applied = permute_cols_in_stack(&first_applied, 1, &second);
+                    for third in ORDERS {
+                        let arranged = permute_cols_in_stack(&second_applied, 2, &third);
+                        search_rows(&arranged, 0, 0, &mut [false; 9], [0; 10], 1, &mut best);
+                    }
+                }
+            }
+        }
+    }
+    Some(best)
+}
+
+/// Fills row `depth` of the result with each row of `grid` allowed there: at
+/// the start of a band any unused row, which opens its band, otherwise an
+/// unused row of the open band.
+fn search_rows(
+    grid: &Grid,
+    depth: usize,
+    band: usize,
+    used: &mut [bool; 9],
+    labels: [i32; 10],
+    next_label: i32,
+    best: &mut Grid,
+) {
+    if depth == 9 {
+        return;
+    }
+    let candidates: Vec<usize> = if depth.is_multiple_of(3) {
+        (0..9).filter(|row| !used[*row]).collect()
+    } else {
+        (band * 3..band * 3 + 3).filter(|row| !used[*row]).collect()
+    };
+
+    for row in candidates {
+        let mut row_labels = labels;
+        let mut row_next = next_label;
+        let mut relabelled = [0; 9];
+        for j in 0..9 {
+            let value = grid[row][j] as usize;
+            if value != 0 {
+                if row_labels[value] == 0 {
+                    row_labels[value] = row_next;
+                    row_next += 1;
+                }
+                relabelled[j] = row_labels[value];
+            }
+        }
+        if relabelled[..] > best[depth][..] {
+            continue;
+        }
+        if relabelled[..] < best[depth][..] {
+            best[depth] = relabelled.to_vec();
+            for later in best.iter_mut().skip(depth + 1) {
+                *later = vec![UNSET; 9];
+            }
+        }
+        used[row] = true;
+        search_rows(grid, depth + 1, row / 3, used, row_labels, row_next, best);
+        used[row] = false;
+    }
+}
+
+/// A stable 64-bit fingerprint: FNV-1a of the minlex form in one-line format.
+/// Equivalent puzzles share a fingerprint.
+pub fn fingerprint(puzzle: &Grid) -> Option<u64> {
+    let form = minlex(puzzle)?;
+    Some(fnv1a(SudokuBoard::to_puzzle_string(&form).as_bytes()))
+}
+
+#[test]
+fn equivalent_puzzles_share_a_form() {
+    use crate::rng::Rng;
+    use crate::transform::Transformation;
+
+    let board = SudokuBoard::from_puzzle([
+        [0, 3, 0, 8, 0, 7, 0, 0, 5],
+        [0, 0, 0, 0, 0, 5, 0, 0, 3],
+        [0, 0, 0, 6, 0, 0, 1, 0, 0],
+        [6, 0, 0, 4, 0, 0, 2, 0, 0],
+        [2, 0, 0, 0, 0, 0, 4, 8, 9],
+        [0, 8, 0, 0, 0, 0, 0, 3, 0],
+        [0, 0, 2, 7, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 6, 0, 0, 0],
+        [0, 9, 7, 0, 0, 0, 0, 4, 2],
+    ]);
+    let puzzle = &board.puzzle;
+    let canonical = minlex(puzzle).unwrap();
+    assert_eq!(minlex(&canonical), Some(canonical.clone()));
+
+    let mut rng = Rng::new(36);
+    for _ in 0..3 {
+        let variant = Transformation::random(board.box_shape.unwrap(), &mut rng).apply(puzzle);
+        assert_eq!(minlex(&variant), Some(canonical.clone()));
+        assert_eq!(fingerprint(&variant), fingerprint(puzzle));
+    }
+
+    let mut different = puzzle.clone();
+    different[0][1] = 0;
+    assert_ne!(fingerprint(&different), fingerprint(puzzle));
+    assert_eq!(minlex(&vec![vec![0; 4]; 4]), None);
+}
@@ -0,0 +1,120 @@
+use crate::generator::{self, GenerateError, GeneratedPuzzle, GeneratorOptions};
+use crate::hash::fnv1a;
+use crate::rating::Difficulty;
+use crate::symmetry::Symmetry;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PuzzleDate {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl PuzzleDate {
+    pub fn new(year: i32, month: u32, day: u32) -> Result<PuzzleDate, DailyError> {
+        let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+        let days_in_month = match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if leap => 29,
+            2 => 28,
+            _ => return Err(DailyError::InvalidDate),
+        };
+        if day == 0 || day > days_in_month {
+            return Err(DailyError::InvalidDate);
+        }
+        Ok(PuzzleDate { year, month, day })
+    }
+
+    /// Parses an ISO date such as "2026-10-19".
+    pub fn parse(date: &str) -> Result<PuzzleDate, DailyError> {
+        let parts: Vec<&str> = date.trim().split('-').collect();
+        if parts.len() != 3 {
+            return Err(DailyError::InvalidDate);
+        }
+        let year = parts[0].parse().map_err(|_| DailyError::InvalidDate)?;
+        let month = parts[1].parse().map_err(|_| DailyError::InvalidDate)?;
+        let day = parts[2].parse().map_err(|_| DailyError::InvalidDate)?;
+        PuzzleDate::new(year, month, day)
+    }
+}
+
+impl fmt::Display for PuzzleDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DailyError {
+    InvalidDate,
+    Generate(GenerateError),
+}
+
+impl fmt::Display for DailyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DailyError::InvalidDate => write!(f, "not a valid calendar date"),
+            DailyError::Generate(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for DailyError {}
+
+/// The seed for a date and difficulty: FNV-1a of e.g. "2026-10-19:hard".
+pub fn daily_seed(date: &PuzzleDate, difficulty: Difficulty) -> u64 {
+    fnv1a(format!("{}:{}", date, difficulty.label()).as_bytes())
+}
+
+/// The puzzle of the day. Generated from the date alone, so every machine
+/// produces the same puzzle; `testdata/daily.txt` pins the output so a
+/// change to the generator that would alter it fails the tests.
+pub fn daily_puzzle(
+    date: &PuzzleDate,
+    difficulty: Difficulty,
+) -> Result<GeneratedPuzzle, DailyError> {
+    let mut options = GeneratorOptions::new(daily_seed(date, difficulty), difficulty);
+    options.symmetry = Symmetry::Rotational180;
+    generator::generate(&options).map_err(DailyError::Generate)
+}
+
+#[test]
+fn rejects_invalid_dates() {
+    assert_eq!(
+        PuzzleDate::parse("2026-02-29"),
+        Err(DailyError::InvalidDate)
+    );
+    assert_eq!(
+        PuzzleDate::parse("2026-13-01"),
+        Err(DailyError::InvalidDate)
+    );
+    assert_eq!(PuzzleDate::parse("yesterday"), Err(DailyError::InvalidDate));
+    assert_eq!(
+        PuzzleDate::parse("2024-02-29").map(|date| date.to_string()),
+        Ok("2024-02-29".to_string())
+    );
+}
+
+#[test]
+fn daily_puzzles_match_golden_file() {
+    use crate::sudoku::SudokuBoard;
+
+    for line in include_str!("../testdata/daily.txt").lines() {
+        if line.starts_with('#') || line.trim().is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let date = PuzzleDate::parse(fields[0]).unwrap();
+        let difficulty = Difficulty::from_label(fields[1]).unwrap();
+        let generated = daily_puzzle(&date, difficulty).unwrap();
+        assert_eq!(
+            SudokuBoard::to_puzzle_string(&generated.board.puzzle),
+            fields[2],
+            "{} {}",
+            date,
+            difficulty
+        );
+    }
+}
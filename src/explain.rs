@@ -0,0 +1,301 @@
+use crate::logic::{SolvePath, Step, Technique, Unit};
+
+/// Names a cell the way players write it, e.g. "r4c6".
+pub fn cell_name(cell: (usize, usize)) -> String {
+    format!("r{}c{}", cell.0 + 1, cell.1 + 1)
+}
+
+/// Reads a cell name like "r4c6", in either case, back into a cell.
+pub fn parse_cell_name(name: &str) -> Option<(usize, usize)> {
+    let (row, col) = name.strip_prefix(['r', 'R'])?.split_once(['c', 'C'])?;
+    let (row, col): (usize, usize) = (row.parse().ok()?, col.parse().ok()?);
+    Some((row.checked_sub(1)?, col.checked_sub(1)?))
+}
+
+pub fn explain_path(path: &SolvePath) -> Vec<String> {
+    path.steps.iter().map(explain_step).collect()
+}
+
+/// Renders a step as one English sentence, e.g.
+/// "Box 5: 7 can only go in r4c6 (hidden single)".
+pub fn explain_step(step: &Step) -> String {
+    let sentence = match step.technique {
+        Technique::HiddenSingle => {
+            let (row, col, value) = step.placement.unwrap();
+            format!(
+                "{}: {} can only go in {}",
+                step.units[0],
+                value,
+                cell_name((row, col))
+            )
+        }
+        Technique::NakedSingle => {
+            let (row, col, value) = step.placement.unwrap();
+            format!(
+                "{}: {} is the only candidate left",
+                cell_name((row, col)),
+                value
+            )
+        }
+        Technique::RuleOf45 => {
+            let (row, col, value) = step.placement.unwrap();
+            format!(
+                "{}: the cage sums leave {} for {}",
+                step.units[0],
+                value,
+                cell_name((row, col))
+            )
+        }
+        Technique::CageCombination => format!(
+            "{}: only {} fit its sum, so {}",
+            step.units[0],
+            join(&digit_names(&step.digits)),
+            removals(step)
+        ),
+        Technique::PairElimination => {
+            let relation = match step.units[0] {
+                Unit::Pair(_, relation) => relation.description(),
+                _ => "related",
+            };
+            format!(
+                "{}: {} and {} must be {}, so {}",
+                step.units[0],
+                cell_name(step.cells[0]),
+                cell_name(step.cells[1]),
+                relation,
+                removals(step)
+            )
+        }
+        Technique::LineElimination => format!(
+            "{}: no way of filling the line puts {}, so {}",
+            step.units[0],
+            ruled_out(step),
+            removals(step)
+        ),
+        Technique::ClueElimination => format!(
+            "{}: no way of meeting the clue puts {}, so {}",
+            step.units[0],
+            ruled_out(step),
+            removals(step)
+        ),
+        Technique::Pointing | Technique::Claiming => format!(
+            "{}: {} is confined to {}, so {}",
+            step.units[0],
+            step.digits[0],
+            step.units[1],
+            removals(step)
+        ),
+        Technique::NakedPair | Technique::NakedTriple => format!(
+            "{}: {} can only hold {}, so {}",
+            step.units[0],
+            join(&cell_names(&step.cells)),
+            join(&digit_names(&step.digits)),
+            removals(step)
+        ),
+        Technique::HiddenPair | Technique::HiddenTriple => format!(
+            "{}: {} can only go in {}, so {}",
+            step.units[0],
+            join(&digit_names(&step.digits)),
+            join(&cell_names(&step.cells)),
+            removals(step)
+        ),
+        Technique::XWing | Technique::Swordfish => {
+            let size = step.units.len() / 2;
+            format!(
+                "{}: {} can only go in {}, so {}",
+                unit_list(&step.units[..size]),
+                step.digits[0],
+                unit_list(&step.units[size..]),
+                removals(step)
+            )
+        }
+        Technique::XYWing => format!(
+            "{} sees {} and {}: either way one of them is {}, so {}",
+            cell_name(step.cells[0]),
+            cell_name(step.cells[1]),
+            cell_name(step.cells[2]),
+            step.digits[2],
+            removals(step)
+        ),
+        Technique::Guess => {
+            let (row, col, value) = step.placement.unwrap();
+            format!(
+                "{}: no technique applies, so try {} out of {}",
+                cell_name((row, col)),
+                value,
+                join(&digit_names(&step.digits))
+            )
+        }
+    };
+    format!("{} ({})", sentence, step.technique)
+}
+
+/// "Rows 2 and 7" for units of one kind.
+fn unit_list(units: &[Unit]) -> String {
+    let kind = match units[0] {
+        Unit::Row(_) => "Rows",
+        Unit::Col(_) => "Columns",
+        Unit::Box(_) => "Boxes",
+        _ => {
+            let names: Vec<String> = units.iter().map(|unit| unit.to_string()).collect();
+            return join(&names);
+        }
+    };
+    let numbers: Vec<String> = units
+        .iter()
+        .map(|unit| match unit {
+            Unit::Row(i) | Unit::Col(i) | Unit::Box(i) => (i + 1).to_string(),
+            other => other.to_string(),
+        })
+        .collect();
+    format!("{} {}", kind, join(&numbers))
+}
+
+type RemovalGroup = (Vec<i32>, Vec<(usize, usize)>);
+
+/// The eliminations grouped by the set of digits removed from each cell.
+fn removal_groups(step: &Step) -> Vec<RemovalGroup> {
+    let mut groups: Vec<RemovalGroup> = Vec::new();
+    let mut cells: Vec<(usize, usize)> = step.eliminations.iter().map(|e| (e.0, e.1)).collect();
+    cells.dedup();
+    for cell in cells {
+        let digits: Vec<i32> = step
+            .eliminations
+            .iter()
+            .filter(|e| (e.0, e.1) == cell)
+            .map(|e| e.2)
+            .collect();
+        match groups.iter_mut().find(|group| group.0 == digits) {
+            Some(group) => group.1.push(cell),
+            None => groups.push((digits, vec![cell])),
+        }
+    }
+    groups
+}
+
+/// "7 can be removed from r1c2 and r1c3", one clause per set of digits.
+fn removals(step: &Step) -> String {
+    let clauses: Vec<String> = removal_groups(step)
+        .iter()
+        .map(|(digits, cells)| {
+            format!(
+                "{} can be removed from {}",
+                join(&digit_names(digits)),
+                join(&cell_names(cells))
+            )
+        })
+        .collect();
+    clauses.join("; ")
+}
+
+/// "3 or 5 in r8c3, or 2 in r8c4": the placements a step rules out.
+fn ruled_out(step: &Step) -> String {
+    let clauses: Vec<String> = removal_groups(step)
+        .iter()
+        .map(|(digits, cells)| {
+            format!(
+                "{} in {}",
+                join_with(&digit_names(digits), "or"),
+                join_with(&cell_names(cells), "or")
+            )
+        })
+        .collect();
+    clauses.join(", or ")
+}
+
+fn cell_names(cells: &[(usize, usize)]) -> Vec<String> {
+    cells.iter().map(|cell| cell_name(*cell)).collect()
+}
+
+fn digit_names(digits: &[i32]) -> Vec<String> {
+    digits.iter().map(|d| d.to_string()).collect()
+}
+
+/// Joins "a", "a and b", "a, b and c".
+fn join(items: &[String]) -> String {
+    join_with(items, "and")
+}
+
+/// Joins like `join` with another final conjunction, "a, b or c".
+fn join_with(items: &[String], conjunction: &str) -> String {
+    match items.len() {
+        0 => String::new(),
+        1 => items[0].clone(),
+        n => format!(
+            "{} {} {}",
+            items[..n - 1].join(", "),
+            conjunction,
+            items[n - 1]
+        ),
+    }
+}
+
+#[test]
+fn explains_hidden_single() {
+    let step = Step {
+        technique: Technique::HiddenSingle,
+        units: vec![Unit::Box(4)],
+        cells: vec![(3, 5)],
+        digits: vec![7],
+        placement: Some((3, 5, 7)),
+        eliminations: Vec::new(),
+    };
+    assert_eq!(
+        explain_step(&step),
+        "Box 5: 7 can only go in r4c6 (hidden single)"
+    );
+}
+
+#[test]
+fn explains_x_wing() {
+    let step = Step {
+        technique: Technique::XWing,
+        units: vec![Unit::Row(1), Unit::Row(6), Unit::Col(2), Unit::Col(7)],
+        cells: vec![(1, 2), (1, 7), (6, 2), (6, 7)],
+        digits: vec![4],
+        placement: None,
+        eliminations: vec![(4, 2, 4), (8, 7, 4)],
+    };
+    assert_eq!(
+        explain_step(&step),
+        "Rows 2 and 7: 4 can only go in Columns 3 and 8, so 4 can be removed from r5c3 and r9c8 (X-Wing)"
+    );
+}
+
+#[test]
+fn explains_line_elimination() {
+    use crate::lines::LineKind;
+
+    let step = Step {
+        technique: Technique::LineElimination,
+        units: vec![Unit::Line(3, LineKind::Whisper)],
+        cells: vec![(7, 1), (7, 2), (7, 3)],
+        digits: vec![3, 5],
+        placement: None,
+        eliminations: vec![(7, 2, 3), (7, 2, 5), (7, 3, 5)],
+    };
+    assert_eq!(
+        explain_step(&step),
+        "German whisper: no way of filling the line puts 3 or 5 in r8c3, or 5 in r8c4, \
+         so 3 and 5 can be removed from r8c3; 5 can be removed from r8c4 (line elimination)"
+    );
+}
+
+#[test]
+fn explains_clue_elimination() {
+    use crate::outside::ClueKind;
+
+    let step = Step {
+        technique: Technique::ClueElimination,
+        units: vec![Unit::Clue(0, ClueKind::Skyscraper)],
+        cells: (0..9).map(|row| (row, 4)).collect(),
+        digits: vec![4],
+        placement: None,
+        eliminations: vec![(0, 4, 6), (2, 4, 3), (2, 4, 5)],
+    };
+    assert_eq!(
+        explain_step(&step),
+        "Skyscraper clue: no way of meeting the clue puts 6 in r1c5, or 3 or 5 in r3c5, \
+         so 6 can be removed from r1c5; 3 and 5 can be removed from r3c5 (outside clue elimination)"
+    );
+}
@@ -0,0 +1,691 @@
+use crate::cages::Cage;
+use crate::json::Json;
+use crate::lines::{Line, LineKind};
+use crate::outside::{ClueKind, OutsideClue, Side};
+use crate::pairs::{PairConstraint, PairRelation};
+use crate::parity::{Parity, ParityCell};
+use crate::peers::PeerRule;
+use crate::regions::{ExtraKind, ExtraUnit, Regions};
+use crate::sudoku::{BoxShape, SudokuBoard, MAX_SIZE};
+use crate::variant::{array, cell, cells, required, unsupported, wrong_type, VariantError};
+
+/// Keys that only change how the puzzle looks or describe it.
+const COSMETIC: [&str; 14] = [
+    "size",
+    "grid",
+    "title",
+    "author",
+    "ruleset",
+    "solution",
+    "text",
+    "line",
+    "rectangle",
+    "circle",
+    "cage",
+    "highlightConflicts",
+    "disabledlogic",
+    "truecandidatesoptions",
+];
+
+/// Builds a board from an f-puzzles export: the puzzle's JSON, its
+/// LZString-compressed base64 form, or a link ending in either (SudokuPad
+/// links add an `fpuzzles` prefix). Givens, jigsaw regions, killer cages,
+/// extra regions, the global rules, thermometers, arrows, renban, whisper
+/// and palindrome lines, Kropki dots, XV, even/odd cells, sandwich sums and
+/// little killer sums are read; any other constraint is reported as
+/// unsupported rather than dropped.
+pub fn import_fpuzzles(text: &str) -> Result<SudokuBoard, VariantError> {
+    let text = text.trim();
+    let text = match text
+        .strip_prefix("https://")
+        .or(text.strip_prefix("http://"))
+    {
+        Some(link) => {
+            let path = link.split_once('/').map_or("", |(_, path)| path);
+            ["?load=", "?puzzleid="]
+                .iter()
+                .find_map(|query| path.strip_prefix(query))
+                .unwrap_or(path)
+        }
+        None => text,
+    };
+    let json = if text.starts_with('{') {
+        Json::parse(text)?
+    } else {
+        if text.starts_with("scl") || text.starts_with("ctc") {
+            return Err(unsupported("SudokuPad's own puzzle format"));
+        }
+        let text = text.strip_prefix("fpuzzles").unwrap_or(text);
+        let decoded = text
+            .replace("%2B", "+")
+            .replace("%2F", "/")
+            .replace("%3D", "=")
+            .replace(' ', "+");
+        Json::parse(&decompress_base64(&decoded).ok_or(VariantError::NotCompressed)?)?
+    };
+    let entries = json.as_object().ok_or(wrong_type("puzzle", "an object"))?;
+
+    let size = match json.get("size") {
+        Some(size) => size
+            .as_i64()
+            .filter(|size| *size >= 0)
+            .ok_or(wrong_type("size", "a whole number"))? as usize,
+        None => 9,
+    };
+    if !(1..=MAX_SIZE).contains(&size) {
+        return Err(VariantError::BadSize { size });
+    }
+    let grid = json.get("grid").ok_or(wrong_type("grid", "given"))?;
+    let rows = grid
+        .as_array()
+        .filter(|rows| rows.len() == size)
+        .ok_or(wrong_type("grid", "a row of cells per row"))?;
+    let mut puzzle = vec![vec![0; size]; size];
+    let mut region_map = vec![vec![None; size]; size];
+    for (i, row) in rows.iter().enumerate() {
+        let row = row
+            .as_array()
+            .filter(|row| row.len() == size)
+            .ok_or(wrong_type("grid", "a row of cells per row"))?;
+        for (j, cell) in row.iter().enumerate() {
+            if cell.get("given").and_then(Json::as_bool) == Some(true) {
+                puzzle[i][j] = cell
+                    .get("value")
+                    .and_then(Json::as_i64)
+                    .filter(|value| (1..=size as i64).contains(value))
+                    .ok_or(wrong_type("value", "a digit"))? as i32;
+            }
+            region_map[i][j] = cell.get("region").and_then(Json::as_i64);
+        }
+    }
+
+    let box_shape = BoxShape::for_size(size);
+    let mut board = if region_map.iter().flatten().any(Option::is_some) {
+        let map = (0..size)
+            .map(|i| {
+                (0..size)
+                    .map(|j| match (region_map[i][j], box_shape) {
+                        (Some(region), _) => Ok(region as i32),
+                        (None, Some(shape)) => {
+                            Ok((i / shape.rows * shape.rows + j / shape.cols) as i32)
+                        }
+                        (None, None) => Err(VariantError::NoBoxShape { size }),
+                    })
+                    .collect()
+            })
+            .collect::<Result<Vec<Vec<i32>>, VariantError>>()?;
+        SudokuBoard::with_regions(Regions::from_map(map)?)
+    } else {
+        SudokuBoard::with_shape(box_shape.ok_or(VariantError::NoBoxShape { size })?)
+    };
+    board.puzzle = puzzle;
+
+    let mut extra_units = Vec::new();
+    let mut pairs = Vec::new();
+    let mut lines = Vec::new();
+    let mut cages = Vec::new();
+    let mut parity_cells = Vec::new();
+    let mut clues = Vec::new();
+    for (key, value) in entries {
+        if COSMETIC.contains(&key.as_str()) || *value == Json::Bool(false) {
+            continue;
+        }
+        match key.as_str() {
+            "diagonal-" => extra_units.push(ExtraUnit::diagonals(size).remove(0)),
+            "diagonal+" => extra_units.push(ExtraUnit::diagonals(size).remove(1)),
+            "disjointgroups" => {
+                let shape = box_shape.ok_or(unsupported("disjointgroups on this size"))?;
+                extra_units.extend(ExtraUnit::disjoint_groups(shape));
+            }
+            "antiknight" => SudokuBoard::add_peer_rule(&mut board, PeerRule::AntiKnight),
+            "antiking" => SudokuBoard::add_peer_rule(&mut board, PeerRule::AntiKing),
+            "nonconsecutive" => pairs.extend(PairConstraint::non_consecutive(size)),
+            "extraregion" => {
+                for (index, region) in array(value, key)?.iter().enumerate() {
+                    extra_units.push(ExtraUnit {
+                        kind: ExtraKind::Custom(index),
+                        cells: cells_at(region, "cells")?,
+                    });
+                }
+            }
+            "killercage" => {
+                for cage in array(value, key)? {
+                    let sum = number(cage, "value")
+                        .map_err(|_| unsupported("killercage without a sum"))?;
+                    cages.push(Cage::new(cells_at(cage, "cells")?, sum));
+                }
+            }
+            "thermometer" | "renban" | "palindrome" | "whispers" => {
+                let kind = match key.as_str() {
+                    "thermometer" => LineKind::Thermo,
+                    "renban" => LineKind::Renban,
+                    "palindrome" => LineKind::Palindrome,
+                    _ => LineKind::Whisper,
+                };
+                for item in array(value, key)? {
+                    if kind == LineKind::Whisper
+                        && item.get("value").is_some()
+                        && number(item, "value")? != 5
+                    {
+                        return Err(unsupported("whispers with a difference other than 5"));
+                    }
+                    for line in item.get("lines").and_then(Json::as_array).unwrap_or(&[]) {
+                        lines.push(Line::new(kind, cells(line, "lines")?));
+                    }
+                }
+            }
+            "arrow" => {
+                for arrow in array(value, key)? {
+                    let circle = match cells_at(arrow, "cells")?[..] {
+                        [circle] => circle,
+                        _ => return Err(unsupported("arrow with a circle of several cells")),
+                    };
+                    for line in arrow.get("lines").and_then(Json::as_array).unwrap_or(&[]) {
+                        let mut arrow_cells = vec![circle];
+                        arrow_cells.extend(
+                            cells(line, "lines")?
+                                .into_iter()
+                                .filter(|cell| *cell != circle),
+                        );
+                        lines.push(Line::new(LineKind::Arrow, arrow_cells));
+                    }
+                }
+            }
+            "difference" | "ratio" | "xv" => {
+                for mark in array(value, key)? {
+                    let relation = match (key.as_str(), mark.get("value").and_then(Json::as_str)) {
+                        ("difference", None | Some("1")) => PairRelation::Consecutive,
+                        ("ratio", None | Some("2")) => PairRelation::Ratio,
+                        ("xv", Some("X" | "x")) => PairRelation::X,
+                        ("xv", Some("V" | "v")) => PairRelation::V,
+                        (_, Some(value)) => {
+                            return Err(unsupported(&format!("{} of {}", key, value)))
+                        }
+                        (_, None) => return Err(wrong_type("value", "X or V")),
+                    };
+                    match cells_at(mark, "cells")?[..] {
+                        [a, b] => pairs.push(PairConstraint::new(a, b, relation)),
+                        _ => return Err(wrong_type("cells", "two cells")),
+                    }
+                }
+            }
+            "negative" => {}
+            "sandwichsum" | "littlekillersum" => {
+                for clue in array(value, key)? {
+                    clues.push(outside_clue(key, clue, size)?);
+                }
+            }
+            "even" | "odd" => {
+                let parity = if key == "even" {
+                    Parity::Even
+                } else {
+                    Parity::Odd
+                };
+                for mark in array(value, key)? {
+                    let name = mark
+                        .get("cell")
+                        .and_then(Json::as_str)
+                        .ok_or(wrong_type("cell", "a cell like R1C1"))?;
+                    parity_cells.push(ParityCell::new(cell(name)?, parity));
+                }
+            }
+            _ => {
+                if value.as_array().is_some_and(|items| items.is_empty()) {
+                    continue;
+                }
+                return Err(unsupported(key));
+            }
+        }
+    }
+
+    let negative: Vec<&str> = json
+        .get("negative")
+        .and_then(Json::as_array)
+        .unwrap_or(&[])
+        .iter()
+        .filter_map(Json::as_str)
+        .collect();
+    let mut unmarked = Vec::new();
+    match (
+        negative.contains(&"difference"),
+        negative.contains(&"ratio"),
+    ) {
+        (true, true) => {
+            let dots: Vec<PairConstraint> = pairs
+                .iter()
+                .filter(|pair| {
+                    matches!(
+                        pair.relation,
+                        PairRelation::Consecutive | PairRelation::Ratio
+                    )
+                })
+                .copied()
+                .collect();
+            unmarked.extend(PairConstraint::kropki_negative(size, &dots));
+        }
+        (false, false) => {}
+        _ => return Err(unsupported("negative constraint on only one kind of dot")),
+    }
+    if negative.contains(&"xv") {
+        let marks: Vec<PairConstraint> = pairs
+            .iter()
+            .filter(|pair| matches!(pair.relation, PairRelation::X | PairRelation::V))
+            .copied()
+            .collect();
+        unmarked.extend(PairConstraint::xv_negative(size, &marks));
+    }
+    pairs.extend(unmarked);
+
+    SudokuBoard::add_extra_units(&mut board, extra_units)?;
+    SudokuBoard::add_cages(&mut board, cages)?;
+    SudokuBoard::add_pair_constraints(&mut board, pairs)?;
+    SudokuBoard::add_lines(&mut board, lines)?;
+    SudokuBoard::add_parity_cells(&mut board, parity_cells)?;
+    SudokuBoard::add_outside_clues(&mut board, clues)?;
+    Ok(board)
+}
+
+/// A number stored under `key`; f-puzzles writes most numbers as strings.
+fn number(json: &Json, key: &str) -> Result<i32, VariantError> {
+    let value = required(json, key)?;
+    value
+        .as_i64()
+        .or_else(|| value.as_str().and_then(|text| text.trim().parse().ok()))
+        .map(|number| number as i32)
+        .ok_or(wrong_type(key, "a number"))
+}
+
+/// A sandwich or little killer clue. f-puzzles puts these in the ring of
+/// cells around the grid, so R0C3 sits above column 3 and R3C0 left of row
+/// 3; a little killer's `direction` (UR, UL, DR or DL) points from there
+/// diagonally into the grid.
+fn outside_clue(key: &str, clue: &Json, size: usize) -> Result<OutsideClue, VariantError> {
+    let name = required(clue, "cell")?
+        .as_str()
+        .ok_or(wrong_type("cell", "a cell like R0C1"))?;
+    let bad_cell = || VariantError::BadCell {
+        name: name.to_string(),
+    };
+    let (row, col) = name
+        .strip_prefix(['r', 'R'])
+        .and_then(|rest| rest.split_once(['c', 'C']))
+        .ok_or_else(bad_cell)?;
+    let row: i32 = row.parse::<i32>().map_err(|_| bad_cell())? - 1;
+    let col: i32 = col.parse::<i32>().map_err(|_| bad_cell())? - 1;
+    let outside = size as i32;
+    let side = match (row, col) {
+        (-1, _) => Side::Top,
+        (row, _) if row == outside => Side::Bottom,
+        (_, -1) => Side::Left,
+        (_, col) if col == outside => Side::Right,
+        _ => return Err(wrong_type("cell", "a cell outside the grid")),
+    };
+    let (kind, (first_row, first_col)) = if key == "littlekillersum" {
+        let (dr, dc) = match required(clue, "direction")?.as_str() {
+            Some("UR") => (-1, 1),
+            Some("UL") => (-1, -1),
+            Some("DR") => (1, 1),
+            Some("DL") => (1, -1),
+            _ => return Err(wrong_type("direction", "UR, UL, DR or DL")),
+        };
+        let heading = match side {
+            Side::Top | Side::Bottom if dc > 0 => Side::Right,
+            Side::Top | Side::Bottom => Side::Left,
+            _ if dr > 0 => Side::Bottom,
+            _ => Side::Top,
+        };
+        let inward = match side {
+            Side::Top => dr > 0,
+            Side::Bottom => dr < 0,
+            Side::Left => dc > 0,
+            Side::Right => dc < 0,
+        };
+        if !inward {
+            return Err(wrong_type("direction", "a direction into the grid"));
+        }
+        (ClueKind::LittleKiller(heading), (row + dr, col + dc))
+    } else {
+        (ClueKind::Sandwich, (row, col))
+    };
+    let index = match side {
+        Side::Top | Side::Bottom => first_col,
+        Side::Left | Side::Right => first_row,
+    };
+    if !(0..outside).contains(&index) {
+        return Err(bad_cell());
+    }
+    Ok(OutsideClue::new(
+        kind,
+        side,
+        index as usize,
+        number(clue, "value")?,
+    ))
+}
+
+/// The cells listed under `key`.
+fn cells_at(json: &Json, key: &str) -> Result<Vec<(usize, usize)>, VariantError> {
+    cells(required(json, key)?, key)
+}
+
+/// Undoes LZString's `compressToBase64`, the encoding f-puzzles uses for
+/// its links and exports.
+pub fn decompress_base64(input: &str) -> Option<String> {
+    const KEYS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/=";
+    let values = input
+        .bytes()
+        .map(|byte| KEYS.iter().position(|key| *key == byte).map(|v| v as u32))
+        .collect::<Option<Vec<u32>>>()?;
+    if values.is_empty() {
+        return None;
+    }
+    let mut bits = BitReader {
+        values,
+        index: 0,
+        position: 32,
+    };
+
+    let mut dictionary: Vec<Vec<u16>> = vec![Vec::new(); 3];
+    let mut enlarge_in = 4;
+    let mut num_bits = 3;
+    let first = match bits.read(2) {
+        0 => bits.read(8),
+        1 => bits.read(16),
+        _ => return Some(String::new()),
+    };
+    let mut word = vec![first as u16];
+    dictionary.push(word.clone());
+    let mut result = word.clone();
+    loop {
+        if bits.index >= bits.values.len() {
+            return None;
+        }
+        let mut code = bits.read(num_bits) as usize;
+        match code {
+            0 | 1 => {
+                let char_bits = if code == 0 { 8 } else { 16 };
+                dictionary.push(vec![bits.read(char_bits) as u16]);
+                code = dictionary.len() - 1;
+                enlarge_in -= 1;
+            }
+            2 => return String::from_utf16(&result).ok(),
+            _ => {}
+        }
+        if enlarge_in == 0 {
+            enlarge_in = 1 << num_bits;
+            num_bits += 1;
+        }
+        let entry = match dictionary.get(code) {
+            Some(entry) => entry.clone(),
+            None if code == dictionary.len() => {
+                let mut entry = word.clone();
+                entry.push(word[0]);
+                entry
+            }
+            None => return None,
+        };
+        result.extend(&entry);
+        let mut added = word;
+        added.push(entry[0]);
+        dictionary.push(added);
+        enlarge_in -= 1;
+        if enlarge_in == 0 {
+            enlarge_in = 1 << num_bits;
+            num_bits += 1;
+        }
+        word = entry;
+    }
+}
+
+/// Reads LZString's bit stream, six bits per base64 character, most
+/// significant first within each character but least significant first
+/// within each value.
+struct BitReader {
+    values: Vec<u32>,
+    index: usize,
+    position: u32,
+}
+
+impl BitReader {
+    fn read(&mut self, count: u32) -> u32 {
+        let mut bits = 0;
+        for power in 0..count {
+            let value = self.values.get(self.index).copied().unwrap_or(0);
+            if value & self.position != 0 {
+                bits |= 1 << power;
+            }
+            self.position >>= 1;
+            if self.position == 0 {
+                self.position = 32;
+                self.index += 1;
+            }
+        }
+        bits
+    }
+}
+
+#[test]
+fn decompresses_lzstring() {
+    assert_eq!(
+        decompress_base64("BIUwNmD2A0AEDukBOYAmBCWBDARr/eOsAl7ILwbgAHtA"),
+        Some("Hello, world! ababababab é 😀".to_string())
+    );
+    assert_eq!(decompress_base64("not base64!"), None);
+    assert_eq!(decompress_base64(""), None);
+}
+
+#[test]
+fn imports_fpuzzles_exports() {
+    use crate::logic::{self, Technique, Unit};
+
+    let export = include_str!("../testdata/fpuzzles_crossroads.txt");
+    let json = Json::parse(&decompress_base64(export.trim()).unwrap()).unwrap();
+    assert_eq!(json.get("title").and_then(Json::as_str), Some("Crossroads"));
+    assert_eq!(json.get("highlightConflicts"), Some(&Json::Bool(true)));
+    let grid = json.get("grid").and_then(Json::as_array).unwrap();
+    let first_row = grid[0].as_array().unwrap();
+    assert!(first_row[0].get("centerPencilMarks").is_some());
+    assert_eq!(first_row[4].get("value").and_then(Json::as_i64), Some(8));
+    assert_eq!(first_row[4].get("given"), None);
+
+    let board = import_fpuzzles(export).unwrap();
+    // Pencil marks, colours and digits entered without being givens are
+    // not part of the puzzle.
+    assert_eq!(
+        SudokuBoard::to_puzzle_string(&board.puzzle),
+        ".................2............8.....16.......7......5.43..68...5................."
+    );
+    assert_eq!(board.lines.len(), 5);
+    assert_eq!(board.lines[0].kind, LineKind::Arrow);
+    assert_eq!(board.lines[0].cells, vec![(6, 7), (7, 8), (6, 8)]);
+    assert_eq!(board.cages[0].cells, vec![(8, 1), (8, 2)]);
+    assert_eq!(board.cages[0].sum, 8);
+    assert_eq!(board.pair_constraints.len(), 4);
+    assert_eq!(board.parity_cells.len(), 2);
+    assert_eq!(
+        board.outside_clues,
+        vec![
+            OutsideClue::new(ClueKind::LittleKiller(Side::Right), Side::Top, 2, 30),
+            OutsideClue::new(ClueKind::Sandwich, Side::Left, 8, 0),
+        ]
+    );
+    // The black dot beside the 6 in r5c2 leaves only 3 in r5c3, and the
+    // palindrome copies the 3 in r7c2 into r9c4.
+    assert_eq!(board.possible_solutions[4][2], vec![3]);
+    assert_eq!(board.possible_solutions[8][3], vec![3]);
+
+    assert_eq!(SudokuBoard::count_solutions(&board, 2), 1);
+    let path = logic::solve_path(&board);
+    assert!(path.solved());
+    let step = |technique: Technique, unit: Unit| {
+        path.steps
+            .iter()
+            .find(|step| step.technique == technique && step.units == [unit])
+            .unwrap()
+    };
+    assert_eq!(
+        step(
+            Technique::PairElimination,
+            Unit::Pair(0, PairRelation::Consecutive)
+        )
+        .eliminations,
+        vec![(5, 1, 2), (5, 1, 4)]
+    );
+    assert!(
+        !step(Technique::LineElimination, Unit::Line(0, LineKind::Arrow))
+            .eliminations
+            .is_empty()
+    );
+    assert_eq!(
+        step(
+            Technique::ClueElimination,
+            Unit::Clue(0, ClueKind::LittleKiller(Side::Right))
+        )
+        .eliminations,
+        vec![(2, 4, 3), (2, 4, 8), (2, 4, 9), (3, 5, 7)]
+    );
+    let solution: Vec<i32> = json
+        .get("solution")
+        .and_then(Json::as_array)
+        .unwrap()
+        .iter()
+        .map(|digit| digit.as_i64().unwrap() as i32)
+        .collect();
+    assert_eq!(path.result.puzzle.concat(), solution);
+
+    for link in [
+        "https://sudokupad.app/fpuzzles",
+        "https://sudokupad.app/?puzzleid=fpuzzles",
+        "https://www.f-puzzles.com/?load=",
+    ] {
+        let link = format!("{}{}", link, export.trim());
+        assert_eq!(import_fpuzzles(&link).unwrap().lines, board.lines);
+    }
+
+    let empty = format!(
+        "[{}]",
+        vec![format!("[{}]", ["{}"; 4].join(",")); 4].join(",")
+    );
+    let board = import_fpuzzles(&format!(
+        r#"{{"size": 4, "grid": {}, "antiking": true, "diagonal-": true,
+            "negative": ["ratio", "difference"], "difference": [{{"cells": ["R1C1", "R1C2"]}}]}}"#,
+        empty
+    ))
+    .unwrap();
+    assert_eq!(board.extra_units[0].kind, ExtraKind::MainDiagonal);
+    assert_eq!(board.peer_rules, vec![PeerRule::AntiKing]);
+    assert_eq!(board.pair_constraints.len(), 24);
+
+    let jigsaw = (0..4)
+        .map(|row| {
+            format!(
+                "[{}]",
+                vec![format!(r#"{{"region": {}}}"#, row); 4].join(",")
+            )
+        })
+        .collect::<Vec<String>>()
+        .join(",");
+    let board = import_fpuzzles(&format!(r#"{{"size": 4, "grid": [{}]}}"#, jigsaw)).unwrap();
+    assert_eq!(board.regions.region(0, 3), 0);
+    assert_eq!(board.regions.region(3, 0), 3);
+
+    let wide = |cell: &str| {
+        let row = format!("[{}]", vec![cell; 26].join(","));
+        format!(r#"{{"size": 26, "grid": [{}]}}"#, vec![row; 26].join(","))
+    };
+    for export in [wide("{}"), wide(r#"{"region": 0}"#)] {
+        assert_eq!(
+            import_fpuzzles(&export).err(),
+            Some(VariantError::BadSize { size: 26 })
+        );
+    }
+    assert_eq!(
+        import_fpuzzles(r#"{"size": 0, "grid": []}"#).err(),
+        Some(VariantError::BadSize { size: 0 })
+    );
+
+    let board = import_fpuzzles(&format!(
+        r#"{{"size": 4, "grid": {}, "sandwichsum": [{{"cell": "R0C2", "value": "0"}},
+            {{"cell": "R3C0", "value": 5}}], "littlekillersum": [
+            {{"cell": "R0C1", "direction": "DR", "value": "10"}},
+            {{"cell": "R5C3", "direction": "UL", "value": "7"}},
+            {{"cell": "R2C5", "direction": "DL", "value": "3"}}]}}"#,
+        empty
+    ))
+    .unwrap();
+    assert_eq!(
+        board.outside_clues,
+        vec![
+            OutsideClue::new(ClueKind::Sandwich, Side::Top, 1, 0),
+            OutsideClue::new(ClueKind::Sandwich, Side::Left, 2, 5),
+            OutsideClue::new(ClueKind::LittleKiller(Side::Right), Side::Top, 1, 10),
+            OutsideClue::new(ClueKind::LittleKiller(Side::Left), Side::Bottom, 1, 7),
+            OutsideClue::new(ClueKind::LittleKiller(Side::Bottom), Side::Right, 2, 3),
+        ]
+    );
+    assert_eq!(
+        board.outside_clues[2].cells(4),
+        vec![(0, 1), (1, 2), (2, 3)]
+    );
+    assert_eq!(board.outside_clues[2].position(4), (-1, 0));
+    assert_eq!(board.outside_clues[3].cells(4), vec![(3, 1), (2, 0)]);
+    assert_eq!(board.outside_clues[3].position(4), (4, 2));
+    assert_eq!(board.outside_clues[4].cells(4), vec![(2, 3), (3, 2)]);
+    assert_eq!(board.outside_clues[4].position(4), (1, 4));
+    assert_eq!(
+        import_fpuzzles(&format!(
+            r#"{{"size": 4, "grid": {}, "littlekillersum": [
+                {{"cell": "R0C1", "direction": "UR", "value": "5"}}]}}"#,
+            empty
+        ))
+        .err(),
+        Some(VariantError::WrongType {
+            key: "direction".to_string(),
+            expected: "a direction into the grid"
+        })
+    );
+    assert_eq!(
+        import_fpuzzles(&format!(
+            r#"{{"size": 4, "grid": {}, "quadruple": [{{"cells": ["R1C1", "R1C2", "R2C1", "R2C2"],
+                "values": [1, 2]}}]}}"#,
+            empty
+        ))
+        .err(),
+        Some(VariantError::Unsupported {
+            constraint: "quadruple".to_string()
+        })
+    );
+    assert_eq!(
+        import_fpuzzles(&format!(r#"{{"size": 4, "grid": {}, "clone": []}}"#, empty)).err(),
+        None
+    );
+    assert_eq!(
+        import_fpuzzles(&format!(
+            r#"{{"size": 4, "grid": {}, "ratio": [{{"cells": ["R1C1", "R1C2"], "value": "3"}}]}}"#,
+            empty
+        ))
+        .err(),
+        Some(VariantError::Unsupported {
+            constraint: "ratio of 3".to_string()
+        })
+    );
+    assert_eq!(
+        import_fpuzzles(&format!(
+            r#"{{"size": 4, "grid": {}, "thermometer": [{{"lines": [["R1C1", "X2"]]}}]}}"#,
+            empty
+        ))
+        .err(),
+        Some(VariantError::BadCell {
+            name: "X2".to_string()
+        })
+    );
+    assert_eq!(
+        import_fpuzzles("sclN4IgzglgXgpiBcBOANCALhNAbO8QBkIA7").err(),
+        Some(VariantError::Unsupported {
+            constraint: "SudokuPad's own puzzle format".to_string()
+        })
+    );
+    assert_eq!(
+        import_fpuzzles("not an export").err(),
+        Some(VariantError::NotCompressed)
+    );
+}
@@ -0,0 +1,497 @@
+use crate::logic::{self, Technique};
+use crate::rating::{self, Difficulty, Rating};
+use crate::rng::Rng;
+use crate::sudoku::{BoxShape, Grid, SudokuBoard};
+use crate::symmetry::Symmetry;
+use std::fmt;
+
+#[derive(Debug, Clone)]
+pub struct GeneratorOptions {
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    /// Clues are removed a whole orbit at a time so the givens keep this symmetry.
+    pub symmetry: Symmetry,
+    /// How many complete grids to try before giving up on the band.
+    pub max_attempts: usize,
+    pub box_shape: BoxShape,
+}
+
+impl GeneratorOptions {
+    pub fn new(seed: u64, difficulty: Difficulty) -> GeneratorOptions {
+        GeneratorOptions {
+            seed,
+            difficulty,
+            symmetry: Symmetry::None,
+            max_attempts: 200,
+            box_shape: BoxShape::CLASSIC,
+        }
+    }
+}
+
+/// Options for tutorial puzzles: the logic solver, limited to techniques no
+/// harder than `max`, must solve the puzzle and use `required` on the way.
+#[derive(Debug, Clone)]
+pub struct TechniqueOptions {
+    pub seed: u64,
+    pub required: Technique,
+    pub max: Technique,
+    pub symmetry: Symmetry,
+    pub max_attempts: usize,
+    pub box_shape: BoxShape,
+}
+
+impl TechniqueOptions {
+    pub fn new(seed: u64, required: Technique, max: Technique) -> TechniqueOptions {
+        TechniqueOptions {
+            seed,
+            required,
+            max,
+            symmetry: Symmetry::None,
+            max_attempts: 2000,
+            box_shape: BoxShape::CLASSIC,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GeneratedPuzzle {
+    pub board: SudokuBoard,
+    pub solution: Grid,
+    pub rating: Rating,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GenerateError {
+    NoPuzzleInBand {
+        attempts: usize,
+    },
+    /// No 9x9 sudoku with fewer than 17 givens has a unique solution.
+    MaskTooSparse {
+        givens: usize,
+    },
+    /// Two empty rows in a band, or two empty columns in a stack, can always
+    /// be swapped in the solution, so no puzzle on the mask is unique.
+    MaskHasSwappableLines,
+    /// The mask is not `size` rows of `size` cells.
+    MaskWrongSize {
+        size: usize,
+    },
+    NoUniquePuzzleForMask {
+        attempts: usize,
+    },
+    /// The required technique is harder than the maximum, or is a guess.
+    InvalidTechniqueRange,
+    NoPuzzleForTechnique {
+        attempts: usize,
+    },
+}
+
+impl fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenerateError::NoPuzzleInBand { attempts } => write!(
+                f,
+                "no puzzle in the requested difficulty band after {} attempts",
+                attempts
+            ),
+            GenerateError::MaskTooSparse { givens } => write!(
+                f,
+                "mask has {} givens but a unique puzzle needs at least 17",
+                givens
+            ),
+            GenerateError::MaskHasSwappableLines => write!(
+                f,
+                "mask leaves two rows of a band or two columns of a stack empty"
+            ),
+            GenerateError::MaskWrongSize { size } => {
+                write!(f, "mask is not {} rows of {} cells", size, size)
+            }
+            GenerateError::NoUniquePuzzleForMask { attempts } => write!(
+                f,
+                "no unique puzzle found for the mask after {} attempts",
+                attempts
+            ),
+            GenerateError::InvalidTechniqueRange => write!(
+                f,
+                "required technique must be a logical technique no harder than the maximum"
+            ),
+            GenerateError::NoPuzzleForTechnique { attempts } => write!(
+                f,
+                "no puzzle requiring the technique found after {} attempts",
+                attempts
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GenerateError {}
+
+/// Generates a uniquely solvable puzzle rated at `options.difficulty`. The
+/// same options always produce the same puzzle.
+pub fn generate(options: &GeneratorOptions) -> Result<GeneratedPuzzle, GenerateError> {
+    let mut rng = Rng::new(options.seed);
+    for _ in 0..options.max_attempts {
+        let solution = random_solution(options.box_shape, &mut rng);
+        let puzzle = remove_clues(&solution, options.box_shape, options.symmetry, &mut rng);
+        let board = SudokuBoard::from_grid(puzzle, options.box_shape);
+        let rating = rating::rate(&board);
+        if rating.difficulty == options.difficulty {
+            return Ok(GeneratedPuzzle {
+                board,
+                solution,
+                rating,
+            });
+        }
+    }
+    Err(GenerateError::NoPuzzleInBand {
+        attempts: options.max_attempts,
+    })
+}
+
+/// Generates a unique puzzle that needs `options.required` and nothing
+/// harder than `options.max`.
+pub fn generate_for_technique(
+    options: &TechniqueOptions,
+) -> Result<GeneratedPuzzle, GenerateError> {
+    if options.required > options.max || options.max == Technique::Guess {
+        return Err(GenerateError::InvalidTechniqueRange);
+    }
+    let mut rng = Rng::new(options.seed);
+    for _ in 0..options.max_attempts {
+        let solution = random_solution(options.box_shape, &mut rng);
+        let puzzle = remove_clues(&solution, options.box_shape, options.symmetry, &mut rng);
+        let board = SudokuBoard::from_grid(puzzle, options.box_shape);
+        let path = logic::solve_path_up_to(&board, options.max);
+        if path.solved()
+            && path
+                .steps
+                .iter()
+                .any(|step| step.technique == options.required)
+        {
+            let rating = rating::rate(&board);
+            return Ok(GeneratedPuzzle {
+                board,
+                solution,
+                rating,
+            });
+        }
+    }
+    Err(GenerateError::NoPuzzleForTechnique {
+        attempts: options.max_attempts,
+    })
+}
+
+/// A random complete grid. Boxes along the diagonal share no row or column,
+/// so they can be filled independently before solving for the rest.
+pub fn random_solution(box_shape: BoxShape, rng: &mut Rng) -> Grid {
+    let size = box_shape.size();
+    let diagonal = (size / box_shape.rows).min(size / box_shape.cols);
+    loop {
+        let mut board = SudokuBoard::with_shape(box_shape);
+        for k in 0..diagonal {
+            let mut digits: Vec<i32> = (1..=size as i32).collect();
+            rng.shuffle(&mut digits);
+            for (cell, value) in digits.iter().enumerate() {
+                board.puzzle[k * box_shape.rows + cell / box_shape.cols]
+                    [k * box_shape.cols + cell % box_shape.cols] = *value;
+            }
+        }
+        // Square boxes always extend; rectangular ones occasionally do not.
+        if let Some(solution) = SudokuBoard::find_solutions(&board, 1).pop() {
+            return solution;
+        }
+    }
+}
+
+/// Removes clues one symmetry orbit at a time in random order, keeping each
+/// removal only if the puzzle stays unique. No further orbit can be removed
+/// from the result.
+pub fn remove_clues(
+    solution: &Grid,
+    box_shape: BoxShape,
+    symmetry: Symmetry,
+    rng: &mut Rng,
+) -> Grid {
+    let mut board = SudokuBoard::with_shape(box_shape);
+    board.puzzle = solution.clone();
+    let mut orbits = symmetry.orbits(box_shape.size());
+    rng.shuffle(&mut orbits);
+    for orbit in orbits {
+        for (row, col) in &orbit {
+            board.puzzle[*row][*col] = 0;
+        }
+        if SudokuBoard::count_solutions(&board, 2) != 1 {
+            for (row, col) in &orbit {
+                board.puzzle[*row][*col] = solution[*row][*col];
+            }
+        }
+    }
+    board.puzzle
+}
+
+/// Perturbations tried on each starting grid before restarting from a new one.
+const MASK_SEARCH_STEPS: usize = 1000;
+
+/// Solutions are counted up to this limit when comparing grids for a mask.
+const MASK_SOLUTION_LIMIT: usize = 100;
+
+/// Generates a unique puzzle with boxes of `box_shape` whose givens are
+/// exactly the `true` cells of `mask`.
+///
+/// Hill-climbs over complete grids: each step swaps a chain of digits between
+/// two rows of a band (or two columns of a stack), which keeps the grid
+/// valid, and keeps the change unless the masked puzzle gains solutions.
+pub fn generate_from_mask(
+    mask: &[Vec<bool>],
+    box_shape: BoxShape,
+    seed: u64,
+    max_attempts: usize,
+) -> Result<GeneratedPuzzle, GenerateError> {
+    let size = box_shape.size();
+    if mask.len() != size || mask.iter().any(|row| row.len() != size) {
+        return Err(GenerateError::MaskWrongSize { size });
+    }
+    let givens = mask.iter().flatten().filter(|given| **given).count();
+    if box_shape == BoxShape::CLASSIC && givens < 17 {
+        return Err(GenerateError::MaskTooSparse { givens });
+    }
+    if has_swappable_lines(mask, box_shape) {
+        return Err(GenerateError::MaskHasSwappableLines);
+    }
+
+    let apply_mask = |solution: &Grid| {
+        let mut board = SudokuBoard::with_shape(box_shape);
+        board.puzzle = solution.clone();
+        for (row, mask_row) in board.puzzle.iter_mut().zip(mask) {
+            for (value, given) in row.iter_mut().zip(mask_row) {
+                if !given {
+                    *value = 0;
+                }
+            }
+        }
+        board
+    };
+
+    let mut rng = Rng::new(seed);
+    for _ in 0..max_attempts {
+        let mut solution = random_solution(box_shape, &mut rng);
+        let mut solutions =
+            SudokuBoard::count_solutions(&apply_mask(&solution), MASK_SOLUTION_LIMIT);
+        for _ in 0..MASK_SEARCH_STEPS {
+            if solutions == 1 {
+                let board = apply_mask(&solution);
+                let board = SudokuBoard::from_grid(board.puzzle, box_shape);
+                let rating = rating::rate(&board);
+                return Ok(GeneratedPuzzle {
+                    board,
+                    solution,
+                    rating,
+                });
+            }
+            let mut candidate = solution.clone();
+            swap_chain(&mut candidate, box_shape, &mut rng);
+            let candidate_solutions =
+                SudokuBoard::count_solutions(&apply_mask(&candidate), MASK_SOLUTION_LIMIT);
+            if candidate_solutions <= solutions {
+                solution = candidate;
+                solutions = candidate_solutions;
+            }
+        }
+    }
+    Err(GenerateError::NoUniquePuzzleForMask {
+        attempts: max_attempts,
+    })
+}
+
+fn has_swappable_lines(mask: &[Vec<bool>], box_shape: BoxShape) -> bool {
+    let size = box_shape.size();
+    let empty_row = |i: usize| (0..size).all(|j| !mask[i][j]);
+    let empty_col = |j: usize| (0..size).all(|i| !mask[i][j]);
+    let swappable_rows = (0..size).step_by(box_shape.rows).any(|band| {
+        (band..band + box_shape.rows)
+            .filter(|i| empty_row(*i))
+            .count()
+            >= 2
+    });
+    let swappable_cols = (0..size).step_by(box_shape.cols).any(|stack| {
+        (stack..stack + box_shape.cols)
+            .filter(|j| empty_col(*j))
+            .count()
+            >= 2
+    });
+    swappable_rows || swappable_cols
+}
+
+/// Swaps a digit between two rows of the same band, then keeps swapping
+/// whichever digit that duplicated until both rows are valid again. Columns
+/// and boxes only ever exchange cells within themselves, so the grid stays
+/// valid. Works on columns of a stack instead half of the time, or always
+/// when boxes are one row high.
+fn swap_chain(solution: &mut Grid, box_shape: BoxShape, rng: &mut Rng) {
+    let size = box_shape.size();
+    // A line alone in its band has nothing to swap with.
+    let by_rows = match (box_shape.rows, box_shape.cols) {
+        (1, 1) => return,
+        (1, _) => false,
+        (_, 1) => true,
+        _ => rng.below(2) == 0,
+    };
+    let width = if by_rows {
+        box_shape.rows
+    } else {
+        box_shape.cols
+    };
+    let band = rng.below(size / width) * width;
+    let first = band + rng.below(width);
+    let second = band + (first - band + 1 + rng.below(width - 1)) % width;
+    let cell = |line: usize, k: usize| if by_rows { (line, k) } else { (k, line) };
+
+    let mut k = rng.below(size);
+    let start = solution[cell(first, k).0][cell(first, k).1];
+    loop {
+        let (a, b) = (cell(first, k), cell(second, k));
+        let moved = solution[b.0][b.1];
+        solution[b.0][b.1] = solution[a.0][a.1];
+        solution[a.0][a.1] = moved;
+        if moved == start {
+            break;
+        }
+        k = (0..size)
+            .find(|other| {
+                let c = cell(first, *other);
+                *other != k && solution[c.0][c.1] == moved
+            })
+            .expect("a swapped digit always appears twice in the line");
+    }
+}
+
+#[test]
+fn generates_unique_puzzle_in_band() {
+    let generated = generate(&GeneratorOptions::new(7, Difficulty::Medium)).unwrap();
+    assert_eq!(generated.rating.difficulty, Difficulty::Medium);
+    assert_eq!(SudokuBoard::count_solutions(&generated.board, 2), 1);
+    assert_eq!(
+        SudokuBoard::find_solutions(&generated.board, 1)[0],
+        generated.solution
+    );
+}
+
+#[test]
+fn generates_symmetric_puzzle() {
+    let mut options = GeneratorOptions::new(11, Difficulty::Easy);
+    options.symmetry = Symmetry::Rotational90;
+    let generated = generate(&options).unwrap();
+    let symmetries = crate::symmetry::detect_symmetries(&generated.board.puzzle);
+    assert!(symmetries.contains(&Symmetry::Rotational90));
+    assert!(symmetries.contains(&Symmetry::Rotational180));
+    assert_eq!(SudokuBoard::count_solutions(&generated.board, 2), 1);
+}
+
+#[test]
+fn generates_puzzle_from_mask() {
+    let ring = [
+        "..XXXXX..",
+        ".X.....X.",
+        "X.......X",
+        "X..X.X..X",
+        "X...X...X",
+        "X..X.X..X",
+        "X.......X",
+        ".X.....X.",
+        "..XXXXX..",
+    ];
+    let mask: Vec<Vec<bool>> = ring
+        .iter()
+        .map(|line| line.chars().map(|mark| mark == 'X').collect())
+        .collect();
+    let generated = generate_from_mask(&mask, BoxShape::CLASSIC, 5, 10).unwrap();
+    let givens: Vec<Vec<bool>> = generated
+        .board
+        .puzzle
+        .iter()
+        .map(|row| row.iter().map(|value| *value != 0).collect())
+        .collect();
+    assert_eq!(givens, mask);
+    assert_eq!(SudokuBoard::count_solutions(&generated.board, 2), 1);
+}
+
+#[test]
+fn rejects_unsupportable_masks() {
+    let mut mask = vec![vec![false; 9]; 9];
+    mask[0] = vec![true; 9];
+    assert_eq!(
+        generate_from_mask(&mask, BoxShape::CLASSIC, 1, 1).unwrap_err(),
+        GenerateError::MaskTooSparse { givens: 9 }
+    );
+    for row in mask.iter_mut().take(7) {
+        *row = vec![true; 9];
+    }
+    assert_eq!(
+        generate_from_mask(&mask, BoxShape::CLASSIC, 1, 1).unwrap_err(),
+        GenerateError::MaskHasSwappableLines
+    );
+    assert_eq!(
+        generate_from_mask(&mask[..8], BoxShape::CLASSIC, 1, 1).unwrap_err(),
+        GenerateError::MaskWrongSize { size: 9 }
+    );
+    mask[4].pop();
+    assert_eq!(
+        generate_from_mask(&mask, BoxShape::CLASSIC, 1, 1).unwrap_err(),
+        GenerateError::MaskWrongSize { size: 9 }
+    );
+}
+
+#[test]
+fn swaps_chains_in_boxes_one_cell_wide() {
+    let mut rng = Rng::new(3);
+    for box_shape in [
+        BoxShape::new(1, 4),
+        BoxShape::new(4, 1),
+        BoxShape::new(1, 1),
+    ] {
+        let mut solution = random_solution(box_shape, &mut rng);
+        for _ in 0..20 {
+            swap_chain(&mut solution, box_shape, &mut rng);
+            let board = SudokuBoard::from_grid(solution.clone(), box_shape);
+            assert!(SudokuBoard::validate_board(&board));
+        }
+    }
+}
+
+#[test]
+fn generates_x_wing_tutorial() {
+    let options = TechniqueOptions::new(6, Technique::XWing, Technique::XWing);
+    let generated = generate_for_technique(&options).unwrap();
+    let path = logic::solve_path_up_to(&generated.board, Technique::XWing);
+    assert!(path.solved());
+    assert!(path
+        .steps
+        .iter()
+        .any(|step| step.technique == Technique::XWing));
+    assert!(!logic::solve_path_up_to(&generated.board, Technique::NakedPair).solved());
+
+    let backwards = TechniqueOptions::new(3, Technique::Swordfish, Technique::XWing);
+    assert_eq!(
+        generate_for_technique(&backwards).unwrap_err(),
+        GenerateError::InvalidTechniqueRange
+    );
+}
+
+#[test]
+fn generation_is_reproducible() {
+    let options = GeneratorOptions::new(2024, Difficulty::Easy);
+    let first = generate(&options).unwrap();
+    let second = generate(&options).unwrap();
+    assert_eq!(first.board.puzzle, second.board.puzzle);
+}
+
+#[test]
+fn generates_six_by_six_puzzle() {
+    let mut options = GeneratorOptions::new(37, Difficulty::Easy);
+    options.box_shape = BoxShape::new(2, 3);
+    let generated = generate(&options).unwrap();
+    assert_eq!(generated.board.size(), 6);
+    assert_eq!(SudokuBoard::count_solutions(&generated.board, 2), 1);
+    let solved = SudokuBoard::from_grid(generated.solution, options.box_shape);
+    assert!(SudokuBoard::validate_board(&solved));
+}
@@ -0,0 +1,17 @@
+/// 64-bit FNV-1a. Used wherever a hash must stay the same across platforms
+/// and releases, which `std::hash::DefaultHasher` does not promise.
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xCBF2_9CE4_8422_2325;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01B3);
+    }
+    hash
+}
+
+#[test]
+fn fnv1a_matches_reference_values() {
+    assert_eq!(fnv1a(b""), 0xCBF2_9CE4_8422_2325);
+    assert_eq!(fnv1a(b"a"), 0xAF63_DC4C_8601_EC8C);
+    assert_eq!(fnv1a(b"foobar"), 0x8594_4171_F739_67E8);
+}
@@ -0,0 +1,205 @@
+use crate::explain::{cell_name, explain_step};
+use crate::logic::{self, Step, Technique, Unit};
+use crate::sudoku::SudokuBoard;
+
+/// How much of the next step to give away, from a nudge to the answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HintLevel {
+    Technique = 1,
+    Region = 2,
+    Cells = 3,
+    Placement = 4,
+}
+
+/// A hint about the next simplest step. Fields beyond what `level` reveals
+/// are left empty so callers can render them without filtering.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hint {
+    pub level: HintLevel,
+    pub technique: Technique,
+    pub units: Vec<Unit>,
+    pub cells: Vec<(usize, usize)>,
+    pub placement: Option<(usize, usize, i32)>,
+    pub eliminations: Vec<(usize, usize, i32)>,
+    pub text: String,
+}
+
+/// Returns a hint for the simplest logical step from the current board, or
+/// `None` when the board is full or stuck beyond the known techniques.
+///
+/// Candidates recorded on the board are kept, minus any digit already placed
+/// in a peer; cells with no recorded candidates are recalculated.
+pub fn hint(board: &SudokuBoard, level: HintLevel) -> Option<Hint> {
+    let s_board = refresh_candidates(board);
+    let step = logic::next_step(&s_board)?;
+    Some(hint_for_step(&s_board, &step, level))
+}
+
+pub fn hint_for_step(board: &SudokuBoard, step: &Step, level: HintLevel) -> Hint {
+    let units = highlighted_units(board, step);
+    let text = match level {
+        HintLevel::Technique => format!("Look for {}.", pattern(step.technique)),
+        HintLevel::Region => format!(
+            "Look for {} in {}.",
+            pattern(step.technique),
+            unit_names(&units)
+        ),
+        HintLevel::Cells => format!(
+            "Look for {} involving {}.",
+            pattern(step.technique),
+            step.cells
+                .iter()
+                .map(|cell| cell_name(*cell))
+                .collect::<Vec<String>>()
+                .join(", ")
+        ),
+        HintLevel::Placement => explain_step(step),
+    };
+    Hint {
+        level,
+        technique: step.technique,
+        units: if level >= HintLevel::Region {
+            units
+        } else {
+            Vec::new()
+        },
+        cells: if level >= HintLevel::Cells {
+            step.cells.clone()
+        } else {
+            Vec::new()
+        },
+        placement: if level == HintLevel::Placement {
+            step.placement
+        } else {
+            None
+        },
+        eliminations: if level == HintLevel::Placement {
+            step.eliminations.clone()
+        } else {
+            Vec::new()
+        },
+        text,
+    }
+}
+
+/// What to look for when a step uses `technique`, with its article.
+fn pattern(technique: Technique) -> &'static str {
+    match technique {
+        Technique::HiddenSingle => "a hidden single",
+        Technique::NakedSingle => "a naked single",
+        Technique::RuleOf45 => "a rule of 45 deduction",
+        Technique::Pointing => "a pointing pair or triple",
+        Technique::Claiming => "a claiming pair or triple",
+        Technique::CageCombination => "a cage with few possible combinations",
+        Technique::PairElimination => "a pair constraint that removes candidates",
+        Technique::LineElimination => "a line that removes candidates",
+        Technique::ClueElimination => "an outside clue that removes candidates",
+        Technique::NakedPair => "a naked pair",
+        Technique::XWing => "an X-Wing",
+        Technique::HiddenPair => "a hidden pair",
+        Technique::NakedTriple => "a naked triple",
+        Technique::Swordfish => "a swordfish",
+        Technique::HiddenTriple => "a hidden triple",
+        Technique::XYWing => "an XY-Wing",
+        Technique::Guess => "a cell to guess",
+    }
+}
+
+/// The units a step lives in; steps that are not tied to a unit highlight the
+/// boxes of their cells instead.
+fn highlighted_units(board: &SudokuBoard, step: &Step) -> Vec<Unit> {
+    if !step.units.is_empty() {
+        return step.units.clone();
+    }
+    let mut units: Vec<Unit> = Vec::new();
+    for (row, col) in &step.cells {
+        let unit = Unit::Box(SudokuBoard::get_box_index(board, *row, *col));
+        if !units.contains(&unit) {
+            units.push(unit);
+        }
+    }
+    units
+}
+
+fn unit_names(units: &[Unit]) -> String {
+    units
+        .iter()
+        .map(|unit| unit.to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+fn refresh_candidates(board: &SudokuBoard) -> SudokuBoard {
+    let mut s_board = board.clone();
+    let fresh = SudokuBoard::calc_possible_solutions(board);
+    for (i, row) in s_board.possible_solutions.iter_mut().enumerate() {
+        for (j, candidates) in row.iter_mut().enumerate() {
+            if candidates.is_empty() {
+                *candidates = fresh[i][j].clone();
+            } else {
+                candidates.retain(|value| fresh[i][j].contains(value));
+            }
+        }
+    }
+    s_board
+}
+
+#[test]
+fn hints_are_graduated() {
+    let board = SudokuBoard::from_puzzle([
+        [0, 3, 0, 8, 0, 7, 0, 0, 5],
+        [0, 0, 0, 0, 0, 5, 0, 0, 3],
+        [0, 0, 0, 6, 0, 0, 1, 0, 0],
+        [6, 0, 0, 4, 0, 0, 2, 0, 0],
+        [2, 0, 0, 0, 0, 0, 4, 8, 9],
+        [0, 8, 0, 0, 0, 0, 0, 3, 0],
+        [0, 0, 2, 7, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 6, 0, 0, 0],
+        [0, 9, 7, 0, 0, 0, 0, 4, 2],
+    ]);
+
+    let nudge = hint(&board, HintLevel::Technique).unwrap();
+    assert_eq!(nudge.text, "Look for a hidden single.");
+    assert!(nudge.units.is_empty() && nudge.cells.is_empty());
+
+    let region = hint(&board, HintLevel::Region).unwrap();
+    assert_eq!(region.text, "Look for a hidden single in Box 3.");
+
+    let cells = hint(&board, HintLevel::Cells).unwrap();
+    assert_eq!(cells.cells, vec![(2, 8)]);
+    assert_eq!(cells.placement, None);
+
+    let answer = hint(&board, HintLevel::Placement).unwrap();
+    assert_eq!(answer.placement, Some((2, 8, 4)));
+    assert_eq!(answer.text, "Box 3: 4 can only go in r3c9 (hidden single)");
+}
+
+#[test]
+fn hint_respects_placed_digits() {
+    let mut board = SudokuBoard::from_puzzle([
+        [0, 3, 0, 8, 0, 7, 0, 0, 5],
+        [0, 0, 0, 0, 0, 5, 0, 0, 3],
+        [0, 0, 0, 6, 0, 0, 1, 0, 0],
+        [6, 0, 0, 4, 0, 0, 2, 0, 0],
+        [2, 0, 0, 0, 0, 0, 4, 8, 9],
+        [0, 8, 0, 0, 0, 0, 0, 3, 0],
+        [0, 0, 2, 7, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 6, 0, 0, 0],
+        [0, 9, 7, 0, 0, 0, 0, 4, 2],
+    ]);
+    board.puzzle[2][8] = 4;
+
+    let answer = hint(&board, HintLevel::Placement).unwrap();
+    assert_ne!(answer.placement, Some((2, 8, 4)));
+}
+
+#[test]
+fn hint_text_uses_the_right_article() {
+    assert_eq!(pattern(Technique::XWing), "an X-Wing");
+    assert_eq!(pattern(Technique::XYWing), "an XY-Wing");
+    assert_eq!(pattern(Technique::NakedPair), "a naked pair");
+    for technique in Technique::ALL {
+        let (article, _) = pattern(technique).split_once(' ').unwrap();
+        assert!(article == "a" || article == "an");
+    }
+}
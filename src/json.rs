@@ -0,0 +1,370 @@
+use std::fmt;
+
+/// A parsed JSON value. Objects keep their keys in the order written.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+/// How many arrays and objects may nest inside each other, well above any
+/// description or export while keeping the recursive reader off the end of
+/// the stack.
+pub const MAX_DEPTH: usize = 128;
+
+/// Where and why a JSON document could not be read; positions count
+/// characters from zero.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JsonError {
+    UnexpectedEnd,
+    Unexpected {
+        position: usize,
+        found: char,
+    },
+    BadNumber {
+        position: usize,
+    },
+    BadEscape {
+        position: usize,
+    },
+    /// An array or object nested more than `MAX_DEPTH` deep.
+    TooDeep {
+        position: usize,
+    },
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonError::UnexpectedEnd => write!(f, "JSON ends too soon"),
+            JsonError::Unexpected { position, found } => {
+                write!(f, "unexpected '{}' at character {}", found, position + 1)
+            }
+            JsonError::BadNumber { position } => {
+                write!(f, "bad number at character {}", position + 1)
+            }
+            JsonError::BadEscape { position } => {
+                write!(f, "bad escape at character {}", position + 1)
+            }
+            JsonError::TooDeep { position } => write!(
+                f,
+                "more than {} levels of nesting at character {}",
+                MAX_DEPTH,
+                position + 1
+            ),
+        }
+    }
+}
+
+impl std::error::Error for JsonError {}
+
+impl Json {
+    pub fn parse(text: &str) -> Result<Json, JsonError> {
+        let mut reader = Reader {
+            chars: text.chars().collect(),
+            position: 0,
+            depth: 0,
+        };
+        let value = reader.value()?;
+        reader.skip_whitespace();
+        match reader.peek() {
+            None => Ok(value),
+            Some(found) => Err(JsonError::Unexpected {
+                position: reader.position,
+                found,
+            }),
+        }
+    }
+
+    /// The value of `key` if this is an object holding it.
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// The number, if this is a whole number.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Json::Number(number) if number.fract() == 0.0 => Some(*number as i64),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Json::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&[(String, Json)]> {
+        match self {
+            Json::Object(entries) => Some(entries),
+            _ => None,
+        }
+    }
+}
+
+struct Reader {
+    chars: Vec<char>,
+    position: usize,
+    /// Arrays and objects open around the current position.
+    depth: usize,
+}
+
+impl Reader {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Result<char, JsonError> {
+        let c = self.peek().ok_or(JsonError::UnexpectedEnd)?;
+        self.position += 1;
+        Ok(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|c| c.is_whitespace()) {
+            self.position += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), JsonError> {
+        let position = self.position;
+        match self.next()? {
+            found if found == expected => Ok(()),
+            found => Err(JsonError::Unexpected { position, found }),
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, JsonError> {
+        self.skip_whitespace();
+        let position = self.position;
+        match self.peek().ok_or(JsonError::UnexpectedEnd)? {
+            '{' | '[' if self.depth == MAX_DEPTH => Err(JsonError::TooDeep { position }),
+            open @ ('{' | '[') => {
+                self.depth += 1;
+                let value = if open == '{' {
+                    self.object()
+                } else {
+                    self.array()
+                };
+                self.depth -= 1;
+                value
+            }
+            '"' => Ok(Json::String(self.string()?)),
+            '-' | '0'..='9' => self.number(),
+            _ => {
+                for (word, value) in [
+                    ("null", Json::Null),
+                    ("true", Json::Bool(true)),
+                    ("false", Json::Bool(false)),
+                ] {
+                    let end = position + word.len();
+                    if end <= self.chars.len()
+                        && self.chars[position..end].iter().copied().eq(word.chars())
+                    {
+                        self.position = end;
+                        return Ok(value);
+                    }
+                }
+                Err(JsonError::Unexpected {
+                    position,
+                    found: self.chars[position],
+                })
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, JsonError> {
+        self.expect('{')?;
+        let mut entries = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.position += 1;
+            return Ok(Json::Object(entries));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(':')?;
+            entries.push((key, self.value()?));
+            self.skip_whitespace();
+            let position = self.position;
+            match self.next()? {
+                ',' => continue,
+                '}' => return Ok(Json::Object(entries)),
+                found => return Err(JsonError::Unexpected { position, found }),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Json, JsonError> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.position += 1;
+            return Ok(Json::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            let position = self.position;
+            match self.next()? {
+                ',' => continue,
+                ']' => return Ok(Json::Array(items)),
+                found => return Err(JsonError::Unexpected { position, found }),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        self.expect('"')?;
+        let mut text = String::new();
+        loop {
+            let position = self.position;
+            match self.next()? {
+                '"' => return Ok(text),
+                '\\' => {
+                    let escaped = match self.next()? {
+                        '"' => '"',
+                        '\\' => '\\',
+                        '/' => '/',
+                        'b' => '\u{8}',
+                        'f' => '\u{c}',
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+                        'u' => self.unicode_escape(position)?,
+                        _ => return Err(JsonError::BadEscape { position }),
+                    };
+                    text.push(escaped);
+                }
+                c => text.push(c),
+            }
+        }
+    }
+
+    /// The character of a `\u` escape whose backslash is at `position`,
+    /// joining a surrogate pair written as two escapes.
+    fn unicode_escape(&mut self, position: usize) -> Result<char, JsonError> {
+        let high = self.hex_digits(position)?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if self.next()? != '\\' || self.next()? != 'u' {
+                return Err(JsonError::BadEscape { position });
+            }
+            let low = self.hex_digits(position)?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(JsonError::BadEscape { position });
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+        char::from_u32(code).ok_or(JsonError::BadEscape { position })
+    }
+
+    fn hex_digits(&mut self, position: usize) -> Result<u32, JsonError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let digit = self.next()?.to_digit(16);
+            code = code * 16 + digit.ok_or(JsonError::BadEscape { position })?;
+        }
+        Ok(code)
+    }
+
+    fn number(&mut self) -> Result<Json, JsonError> {
+        let start = self.position;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+        {
+            self.position += 1;
+        }
+        let text: String = self.chars[start..self.position].iter().collect();
+        text.parse()
+            .map(Json::Number)
+            .map_err(|_| JsonError::BadNumber { position: start })
+    }
+}
+
+#[test]
+fn reads_json() {
+    let json = Json::parse(
+        r#"{"size": 9, "name": "a\"bé", "cells": [1, -2.5e1, true, null], "empty": {}}"#,
+    )
+    .unwrap();
+    assert_eq!(json.get("size").and_then(Json::as_i64), Some(9));
+    assert_eq!(json.get("name").and_then(Json::as_str), Some("a\"bé"));
+    assert_eq!(
+        json.get("cells").and_then(Json::as_array).unwrap(),
+        &[
+            Json::Number(1.0),
+            Json::Number(-25.0),
+            Json::Bool(true),
+            Json::Null
+        ]
+    );
+    assert_eq!(json.get("empty"), Some(&Json::Object(Vec::new())));
+    assert_eq!(json.get("missing"), None);
+    assert_eq!(
+        Json::parse(r#""\ud83d\ude00""#),
+        Ok(Json::String("😀".to_string()))
+    );
+
+    assert_eq!(Json::parse("[1, 2"), Err(JsonError::UnexpectedEnd));
+    assert_eq!(
+        Json::parse("[1 2]"),
+        Err(JsonError::Unexpected {
+            position: 3,
+            found: '2'
+        })
+    );
+    assert_eq!(
+        Json::parse("1.2.3"),
+        Err(JsonError::BadNumber { position: 0 })
+    );
+    let nested = "[".repeat(MAX_DEPTH) + &"]".repeat(MAX_DEPTH);
+    assert!(Json::parse(&nested).is_ok());
+    assert_eq!(
+        Json::parse(&"[".repeat(200000)),
+        Err(JsonError::TooDeep {
+            position: MAX_DEPTH
+        })
+    );
+    assert_eq!(
+        Json::parse(&format!("{{\"a\": {}", nested)),
+        Err(JsonError::TooDeep {
+            position: MAX_DEPTH + 5
+        })
+    );
+    assert_eq!(
+        Json::parse("{} x"),
+        Err(JsonError::Unexpected {
+            position: 3,
+            found: 'x'
+        })
+    );
+}
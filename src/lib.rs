@@ -0,0 +1,25 @@
+pub mod cages;
+pub mod canonical;
+pub mod daily;
+pub mod explain;
+pub mod fpuzzles;
+pub mod generator;
+pub mod hash;
+pub mod hint;
+pub mod json;
+pub mod lines;
+pub mod logic;
+pub mod minimal;
+pub mod multigrid;
+pub mod outside;
+pub mod pairs;
+pub mod parity;
+pub mod peers;
+pub mod rating;
+pub mod regions;
+pub mod rng;
+pub mod sudoku;
+pub mod symbols;
+pub mod symmetry;
+pub mod transform;
+pub mod variant;
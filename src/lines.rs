@@ -0,0 +1,242 @@
+use std::fmt;
+
+/// The rule a line imposes on the digits along it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LineKind {
+    /// Digits strictly increase from the bulb, the first cell.
+    Thermo,
+    /// The digits on the shaft add up to the digit in the circle, the first
+    /// cell.
+    Arrow,
+    /// A set of distinct consecutive digits in any order.
+    Renban,
+    /// Neighbouring digits differ by at least half the board size, rounded
+    /// up: 5 on a classic board.
+    Whisper,
+    /// The line reads the same from either end.
+    Palindrome,
+}
+
+impl LineKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            LineKind::Thermo => "thermometer",
+            LineKind::Arrow => "arrow",
+            LineKind::Renban => "renban line",
+            LineKind::Whisper => "German whisper",
+            LineKind::Palindrome => "palindrome",
+        }
+    }
+}
+
+impl fmt::Display for LineKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// An ordered path of cells with a rule on its digits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    pub kind: LineKind,
+    pub cells: Vec<(usize, usize)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LineError {
+    /// Lines need at least two cells.
+    TooShort,
+    CellOutOfRange {
+        row: usize,
+        col: usize,
+    },
+    DuplicateCell {
+        row: usize,
+        col: usize,
+    },
+    /// A thermometer or renban line longer than the number of digits.
+    TooLong {
+        cells: usize,
+    },
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LineError::TooShort => write!(f, "line has fewer than two cells"),
+            LineError::CellOutOfRange { row, col } => {
+                write!(f, "r{}c{} is outside the board", row + 1, col + 1)
+            }
+            LineError::DuplicateCell { row, col } => {
+                write!(f, "r{}c{} appears twice on one line", row + 1, col + 1)
+            }
+            LineError::TooLong { cells } => {
+                write!(f, "a line of {} cells cannot hold distinct digits", cells)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LineError {}
+
+impl Line {
+    pub fn new(kind: LineKind, cells: Vec<(usize, usize)>) -> Line {
+        Line { kind, cells }
+    }
+
+    /// Checks that the line fits a board of side `size`.
+    pub fn check(&self, size: usize) -> Result<(), LineError> {
+        if self.cells.len() < 2 {
+            return Err(LineError::TooShort);
+        }
+        for (index, (row, col)) in self.cells.iter().enumerate() {
+            if *row >= size || *col >= size {
+                return Err(LineError::CellOutOfRange {
+                    row: *row,
+                    col: *col,
+                });
+            }
+            if self.cells[..index].contains(&(*row, *col)) {
+                return Err(LineError::DuplicateCell {
+                    row: *row,
+                    col: *col,
+                });
+            }
+        }
+        let distinct = matches!(self.kind, LineKind::Thermo | LineKind::Renban);
+        if distinct && self.cells.len() > size {
+            return Err(LineError::TooLong {
+                cells: self.cells.len(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Whether the digits along the line, in order with 0 for an empty cell,
+    /// can still be completed on a board of side `size`. Exact when every
+    /// cell is filled.
+    pub fn allows(&self, values: &[i32], size: usize) -> bool {
+        let size = size as i32;
+        let filled = || values.iter().enumerate().filter(|(_, value)| **value != 0);
+        match self.kind {
+            LineKind::Thermo => {
+                let last = values.len() as i32 - 1;
+                filled().all(|(i, value)| *value > i as i32 && *value <= size - (last - i as i32))
+                    && filled().all(|(i, a)| {
+                        filled()
+                            .filter(|(j, _)| *j > i)
+                            .all(|(j, b)| b - a >= (j - i) as i32)
+                    })
+            }
+            LineKind::Arrow => {
+                let shaft = &values[1..];
+                let sum: i32 = shaft.iter().sum();
+                let empty = shaft.iter().filter(|value| **value == 0).count() as i32;
+                match values[0] {
+                    0 => sum + empty <= size,
+                    circle => sum + empty <= circle && sum + empty * size >= circle,
+                }
+            }
+            LineKind::Renban => {
+                let digits: Vec<i32> = filled().map(|(_, value)| *value).collect();
+                let distinct = digits
+                    .iter()
+                    .enumerate()
+                    .all(|(i, value)| !digits[..i].contains(value));
+                let spread = match (digits.iter().min(), digits.iter().max()) {
+                    (Some(min), Some(max)) => max - min,
+                    _ => 0,
+                };
+                distinct && spread < values.len() as i32
+            }
+            LineKind::Whisper => {
+                let gap = (size + 1) / 2;
+                values
+                    .windows(2)
+                    .all(|pair| pair[0] == 0 || pair[1] == 0 || (pair[0] - pair[1]).abs() >= gap)
+            }
+            LineKind::Palindrome => {
+                let last = values.len() - 1;
+                (0..values.len()).all(|i| {
+                    values[i] == 0 || values[last - i] == 0 || values[i] == values[last - i]
+                })
+            }
+        }
+    }
+}
+
+#[test]
+fn checks_line_rules() {
+    let thermo = Line::new(LineKind::Thermo, vec![(0, 0), (0, 1), (0, 2)]);
+    assert!(thermo.allows(&[1, 0, 9], 9));
+    assert!(!thermo.allows(&[5, 0, 6], 9));
+    assert!(!thermo.allows(&[0, 0, 2], 9));
+    assert!(!thermo.allows(&[3, 2, 4], 9));
+
+    let arrow = Line::new(LineKind::Arrow, vec![(0, 0), (0, 1), (0, 2)]);
+    assert!(arrow.allows(&[9, 4, 5], 9));
+    assert!(!arrow.allows(&[9, 4, 4], 9));
+    assert!(arrow.allows(&[0, 4, 0], 9));
+    assert!(!arrow.allows(&[0, 8, 2], 9));
+    assert!(!arrow.allows(&[3, 3, 0], 9));
+
+    let renban = Line::new(LineKind::Renban, vec![(0, 0), (1, 0), (2, 0)]);
+    assert!(renban.allows(&[5, 0, 3], 9));
+    assert!(!renban.allows(&[5, 0, 2], 9));
+    assert!(!renban.allows(&[5, 5, 0], 9));
+
+    let whisper = Line::new(LineKind::Whisper, vec![(0, 0), (0, 1), (0, 2)]);
+    assert!(whisper.allows(&[1, 6, 1], 9));
+    assert!(!whisper.allows(&[1, 5, 0], 9));
+
+    let palindrome = Line::new(LineKind::Palindrome, vec![(0, 0), (0, 1), (1, 1), (1, 0)]);
+    assert!(palindrome.allows(&[3, 0, 0, 3], 9));
+    assert!(!palindrome.allows(&[3, 4, 5, 3], 9));
+
+    assert_eq!(
+        Line::new(LineKind::Renban, vec![(0, 0)]).check(9),
+        Err(LineError::TooShort)
+    );
+    assert_eq!(
+        Line::new(LineKind::Thermo, (0..10).map(|i| (0, i)).collect()).check(9),
+        Err(LineError::CellOutOfRange { row: 0, col: 9 })
+    );
+}
+
+#[test]
+fn solves_line_puzzle() {
+    use crate::logic::{self, Technique, Unit};
+    use crate::sudoku::SudokuBoard;
+
+    let mut board = SudokuBoard::from_puzzle_string(
+        "......2....6..5......4...7.....1.......9...3..9..4...6.47.3.9....1.....7.....1.4.",
+    )
+    .unwrap();
+    assert_eq!(board.possible_solutions[1][7], vec![1, 8, 9]);
+    let lines = vec![
+        Line::new(LineKind::Thermo, vec![(4, 7), (3, 6), (2, 7), (1, 7)]),
+        Line::new(LineKind::Arrow, vec![(1, 2), (1, 1), (0, 2)]),
+        Line::new(LineKind::Renban, vec![(3, 1), (4, 1), (4, 2)]),
+        Line::new(LineKind::Whisper, vec![(4, 4), (3, 4), (2, 5), (1, 6)]),
+        Line::new(LineKind::Palindrome, vec![(8, 1), (8, 2), (7, 3), (6, 3)]),
+    ];
+    SudokuBoard::add_lines(&mut board, lines).unwrap();
+    // r2c8 ends a thermometer four cells long, so it is at least 4.
+    assert_eq!(board.possible_solutions[1][7], vec![8, 9]);
+
+    let path = logic::solve_path(&board);
+    assert!(path.solved());
+    let line_step = |kind: LineKind| {
+        path.steps
+            .iter()
+            .find(|step| {
+                step.technique == Technique::LineElimination
+                    && matches!(step.units[..], [Unit::Line(_, k)] if k == kind)
+            })
+            .unwrap()
+    };
+    assert_eq!(line_step(LineKind::Whisper).eliminations, vec![(1, 6, 8)]);
+    for kind in [LineKind::Arrow, LineKind::Renban, LineKind::Palindrome] {
+        assert!(!line_step(kind).eliminations.is_empty());
+    }
+}
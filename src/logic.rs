@@ -0,0 +1,1173 @@
+use crate::cages;
+use crate::lines::LineKind;
+use crate::outside::{ClueKind, OutsideClue};
+use crate::pairs::PairRelation;
+use crate::regions::ExtraKind;
+use crate::sudoku::SudokuBoard;
+use std::fmt;
+
+/// Logical techniques in the order the solver tries them, simplest first.
+/// `Guess` is a placement taken from the backtracking solution when no
+/// technique applies; it is not part of `ALL`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Technique {
+    HiddenSingle,
+    NakedSingle,
+    RuleOf45,
+    Pointing,
+    Claiming,
+    CageCombination,
+    PairElimination,
+    LineElimination,
+    ClueElimination,
+    NakedPair,
+    XWing,
+    HiddenPair,
+    NakedTriple,
+    Swordfish,
+    HiddenTriple,
+    XYWing,
+    Guess,
+}
+
+impl Technique {
+    pub const ALL: [Technique; 16] = [
+        Technique::HiddenSingle,
+        Technique::NakedSingle,
+        Technique::RuleOf45,
+        Technique::Pointing,
+        Technique::Claiming,
+        Technique::CageCombination,
+        Technique::PairElimination,
+        Technique::LineElimination,
+        Technique::ClueElimination,
+        Technique::NakedPair,
+        Technique::XWing,
+        Technique::HiddenPair,
+        Technique::NakedTriple,
+        Technique::Swordfish,
+        Technique::HiddenTriple,
+        Technique::XYWing,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Technique::HiddenSingle => "hidden single",
+            Technique::NakedSingle => "naked single",
+            Technique::RuleOf45 => "rule of 45",
+            Technique::Pointing => "pointing",
+            Technique::Claiming => "claiming",
+            Technique::CageCombination => "cage combination",
+            Technique::PairElimination => "pair elimination",
+            Technique::LineElimination => "line elimination",
+            Technique::ClueElimination => "outside clue elimination",
+            Technique::NakedPair => "naked pair",
+            Technique::XWing => "X-Wing",
+            Technique::HiddenPair => "hidden pair",
+            Technique::NakedTriple => "naked triple",
+            Technique::Swordfish => "swordfish",
+            Technique::HiddenTriple => "hidden triple",
+            Technique::XYWing => "XY-Wing",
+            Technique::Guess => "guess",
+        }
+    }
+}
+
+impl fmt::Display for Technique {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// A row, column or box, indexed from zero. Displayed one-based, e.g. "Box 5".
+/// `Extra` indexes the board's `extra_units`, `Cage` its `cages`, `Pair` its
+/// `pair_constraints`, `Line` its `lines` and `Clue` its `outside_clues`.
+/// Pairs, lines and clues are not units of distinct digits, so `Unit::all`
+/// leaves them out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Unit {
+    Row(usize),
+    Col(usize),
+    Box(usize),
+    Extra(usize, ExtraKind),
+    Cage(usize),
+    Pair(usize, PairRelation),
+    Line(usize, LineKind),
+    Clue(usize, ClueKind),
+}
+
+impl Unit {
+    pub fn all(board: &SudokuBoard) -> Vec<Unit> {
+        let mut units = Vec::new();
+        for i in 0..board.size() {
+            units.push(Unit::Box(i));
+        }
+        for i in 0..board.size() {
+            units.push(Unit::Row(i));
+        }
+        for i in 0..board.size() {
+            units.push(Unit::Col(i));
+        }
+        for (i, extra) in board.extra_units.iter().enumerate() {
+            units.push(Unit::Extra(i, extra.kind));
+        }
+        for i in 0..board.cages.len() {
+            units.push(Unit::Cage(i));
+        }
+        units
+    }
+
+    pub fn cells(&self, board: &SudokuBoard) -> Vec<(usize, usize)> {
+        let size = board.size();
+        match *self {
+            Unit::Row(r) => (0..size).map(|c| (r, c)).collect(),
+            Unit::Col(c) => (0..size).map(|r| (r, c)).collect(),
+            Unit::Box(b) => board.regions.cells(b).to_vec(),
+            Unit::Extra(i, _) => board.extra_units[i].cells.clone(),
+            Unit::Cage(i) => board.cages[i].cells.clone(),
+            Unit::Pair(i, _) => {
+                let pair = &board.pair_constraints[i];
+                vec![pair.a, pair.b]
+            }
+            Unit::Line(i, _) => board.lines[i].cells.clone(),
+            Unit::Clue(i, _) => board.outside_clues[i].cells(size),
+        }
+    }
+
+    pub fn contains(&self, board: &SudokuBoard, cell: (usize, usize)) -> bool {
+        match *self {
+            Unit::Row(r) => cell.0 == r,
+            Unit::Col(c) => cell.1 == c,
+            Unit::Box(b) => SudokuBoard::get_box_index(board, cell.0, cell.1) == b,
+            Unit::Extra(i, _) => board.extra_units[i].cells.contains(&cell),
+            Unit::Cage(i) => board.cages[i].cells.contains(&cell),
+            Unit::Pair(i, _) => board.pair_constraints[i].partner(cell).is_some(),
+            Unit::Line(i, _) => board.lines[i].cells.contains(&cell),
+            Unit::Clue(i, _) => board.outside_clues[i].cells(board.size()).contains(&cell),
+        }
+    }
+
+    /// Whether the unit holds every digit. Hidden singles and hidden subsets
+    /// only apply to complete units; a short extra unit may miss digits.
+    pub fn is_complete(&self, board: &SudokuBoard) -> bool {
+        match *self {
+            Unit::Extra(i, _) => board.extra_units[i].cells.len() == board.size(),
+            Unit::Cage(i) => board.cages[i].cells.len() == board.size(),
+            Unit::Pair(..) | Unit::Line(..) | Unit::Clue(..) => false,
+            _ => true,
+        }
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Unit::Row(r) => write!(f, "Row {}", r + 1),
+            Unit::Col(c) => write!(f, "Column {}", c + 1),
+            Unit::Box(b) => write!(f, "Box {}", b + 1),
+            Unit::Extra(_, kind) => write!(f, "{}", kind),
+            Unit::Cage(i) => write!(f, "Cage {}", i + 1),
+            Unit::Pair(_, relation) => write!(f, "{}", capitalised(relation.name())),
+            Unit::Line(_, kind) => write!(f, "{}", capitalised(kind.name())),
+            Unit::Clue(_, kind) => write!(f, "{}", capitalised(kind.name())),
+        }
+    }
+}
+
+/// One deduction on the way to a solution.
+///
+/// `units` are the regions the pattern lives in, `cells` the cells forming
+/// the pattern and `digits` the digits it is about. A step either places a
+/// digit or removes candidates, never both.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub technique: Technique,
+    pub units: Vec<Unit>,
+    pub cells: Vec<(usize, usize)>,
+    pub digits: Vec<i32>,
+    pub placement: Option<(usize, usize, i32)>,
+    pub eliminations: Vec<(usize, usize, i32)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SolvePath {
+    pub steps: Vec<Step>,
+    pub result: SudokuBoard,
+}
+
+impl SolvePath {
+    pub fn solved(&self) -> bool {
+        SudokuBoard::populated(&self.result) && SudokuBoard::validate_board(&self.result)
+    }
+}
+
+fn capitalised(name: &str) -> String {
+    name[..1].to_uppercase() + &name[1..]
+}
+
+pub fn sees(board: &SudokuBoard, a: (usize, usize), b: (usize, usize)) -> bool {
+    SudokuBoard::is_peer(board, a, b)
+}
+
+/// Solves `board` with logic alone, recording every step taken. Stops when the
+/// grid is full or no technique applies; check `SolvePath::solved`.
+pub fn solve_path(board: &SudokuBoard) -> SolvePath {
+    solve_path_up_to(board, Technique::XYWing)
+}
+
+/// Like `solve_path`, but only uses techniques no harder than `max`.
+pub fn solve_path_up_to(board: &SudokuBoard, max: Technique) -> SolvePath {
+    let mut s_board = board.clone();
+    s_board.possible_solutions = SudokuBoard::calc_possible_solutions(&s_board);
+    let mut steps = Vec::new();
+    while !SudokuBoard::populated(&s_board) {
+        match next_step_up_to(&s_board, max) {
+            Some(step) => {
+                apply_step(&mut s_board, &step);
+                steps.push(step);
+            }
+            None => break,
+        }
+    }
+    SolvePath {
+        steps,
+        result: s_board,
+    }
+}
+
+/// Finds the simplest step available from the board's current candidates.
+pub fn next_step(board: &SudokuBoard) -> Option<Step> {
+    next_step_up_to(board, Technique::XYWing)
+}
+
+pub fn next_step_up_to(board: &SudokuBoard, max: Technique) -> Option<Step> {
+    Technique::ALL
+        .iter()
+        .take_while(|technique| **technique <= max)
+        .find_map(|technique| find_step(board, *technique))
+}
+
+pub fn find_step(board: &SudokuBoard, technique: Technique) -> Option<Step> {
+    match technique {
+        Technique::HiddenSingle => find_hidden_single(board),
+        Technique::NakedSingle => find_naked_single(board),
+        Technique::RuleOf45 => find_rule_of_45(board),
+        Technique::Pointing => find_pointing(board),
+        Technique::Claiming => find_claiming(board),
+        Technique::CageCombination => find_cage_combination(board),
+        Technique::PairElimination => find_pair_elimination(board),
+        Technique::LineElimination => find_line_elimination(board),
+        Technique::ClueElimination => find_clue_elimination(board),
+        Technique::NakedPair => find_naked_subset(board, 2),
+        Technique::NakedTriple => find_naked_subset(board, 3),
+        Technique::HiddenPair => find_hidden_subset(board, 2),
+        Technique::HiddenTriple => find_hidden_subset(board, 3),
+        Technique::XWing => find_fish(board, 2),
+        Technique::Swordfish => find_fish(board, 3),
+        Technique::XYWing => find_xy_wing(board),
+        Technique::Guess => guess_step(board),
+    }
+}
+
+/// Places the solution digit in the unsolved cell with the fewest candidates.
+/// Returns `None` when the board is full or has no solution.
+pub fn guess_step(board: &SudokuBoard) -> Option<Step> {
+    let mut cell: Option<(usize, usize)> = None;
+    for i in 0..board.size() {
+        for j in 0..board.size() {
+            if board.puzzle[i][j] != 0 {
+                continue;
+            }
+            let count = board.possible_solutions[i][j].len();
+            if cell.is_none_or(|(r, c)| count < board.possible_solutions[r][c].len()) {
+                cell = Some((i, j));
+            }
+        }
+    }
+    let (row, col) = cell?;
+    let solution = SudokuBoard::solve_backtracking(board)?;
+    Some(Step {
+        technique: Technique::Guess,
+        units: Vec::new(),
+        cells: vec![(row, col)],
+        digits: board.possible_solutions[row][col].clone(),
+        placement: Some((row, col, solution.puzzle[row][col])),
+        eliminations: Vec::new(),
+    })
+}
+
+pub fn apply_step(board: &mut SudokuBoard, step: &Step) {
+    if let Some((row, col, value)) = step.placement {
+        board.puzzle[row][col] = value;
+        board.possible_solutions[row][col] = Default::default();
+        for i in 0..board.size() {
+            for j in 0..board.size() {
+                if sees(board, (row, col), (i, j)) {
+                    board.possible_solutions[i][j].retain(|v| *v != value);
+                }
+            }
+        }
+    }
+    for (row, col, value) in &step.eliminations {
+        board.possible_solutions[*row][*col] =
+            SudokuBoard::remove_possible_value_from_cell(board, *value, *row, *col);
+    }
+}
+
+fn candidate_cells(board: &SudokuBoard, unit: &Unit, value: i32) -> Vec<(usize, usize)> {
+    unit.cells(board)
+        .into_iter()
+        .filter(|(r, c)| board.possible_solutions[*r][*c].contains(&value))
+        .collect()
+}
+
+fn unit_has_value(board: &SudokuBoard, unit: &Unit, value: i32) -> bool {
+    unit.cells(board)
+        .iter()
+        .any(|(r, c)| board.puzzle[*r][*c] == value)
+}
+
+fn find_hidden_single(board: &SudokuBoard) -> Option<Step> {
+    for unit in Unit::all(board)
+        .into_iter()
+        .filter(|unit| unit.is_complete(board))
+    {
+        for value in 1..=board.size() as i32 {
+            let cells = candidate_cells(board, &unit, value);
+            if cells.len() == 1 && !unit_has_value(board, &unit, value) {
+                let (row, col) = cells[0];
+                return Some(Step {
+                    technique: Technique::HiddenSingle,
+                    units: vec![unit],
+                    cells: vec![(row, col)],
+                    digits: vec![value],
+                    placement: Some((row, col, value)),
+                    eliminations: Vec::new(),
+                });
+            }
+        }
+    }
+    None
+}
+
+fn find_naked_single(board: &SudokuBoard) -> Option<Step> {
+    for i in 0..board.size() {
+        for j in 0..board.size() {
+            if board.puzzle[i][j] == 0 && board.possible_solutions[i][j].len() == 1 {
+                let value = board.possible_solutions[i][j][0];
+                return Some(Step {
+                    technique: Technique::NakedSingle,
+                    units: Vec::new(),
+                    cells: vec![(i, j)],
+                    digits: vec![value],
+                    placement: Some((i, j, value)),
+                    eliminations: Vec::new(),
+                });
+            }
+        }
+    }
+    None
+}
+
+/// Candidates confined to one line inside a box can be removed from the rest of that line.
+fn find_pointing(board: &SudokuBoard) -> Option<Step> {
+    for b in 0..board.size() {
+        let box_unit = Unit::Box(b);
+        for value in 1..=board.size() as i32 {
+            let cells = candidate_cells(board, &box_unit, value);
+            if cells.len() < 2 {
+                continue;
+            }
+            for line in [Unit::Row(cells[0].0), Unit::Col(cells[0].1)] {
+                if !cells.iter().all(|cell| line.contains(board, *cell)) {
+                    continue;
+                }
+                let eliminations: Vec<(usize, usize, i32)> = candidate_cells(board, &line, value)
+                    .into_iter()
+                    .filter(|cell| !box_unit.contains(board, *cell))
+                    .map(|(r, c)| (r, c, value))
+                    .collect();
+                if !eliminations.is_empty() {
+                    return Some(Step {
+                        technique: Technique::Pointing,
+                        units: vec![box_unit, line],
+                        cells,
+                        digits: vec![value],
+                        placement: None,
+                        eliminations,
+                    });
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Candidates confined to one box inside a line can be removed from the rest of that box.
+fn find_claiming(board: &SudokuBoard) -> Option<Step> {
+    for i in 0..board.size() {
+        for line in [Unit::Row(i), Unit::Col(i)] {
+            for value in 1..=board.size() as i32 {
+                let cells = candidate_cells(board, &line, value);
+                if cells.len() < 2 {
+                    continue;
+                }
+                let box_unit = Unit::Box(SudokuBoard::get_box_index(board, cells[0].0, cells[0].1));
+                if !cells.iter().all(|cell| box_unit.contains(board, *cell)) {
+                    continue;
+                }
+                let eliminations: Vec<(usize, usize, i32)> =
+                    candidate_cells(board, &box_unit, value)
+                        .into_iter()
+                        .filter(|cell| !line.contains(board, *cell))
+                        .map(|(r, c)| (r, c, value))
+                        .collect();
+                if !eliminations.is_empty() {
+                    return Some(Step {
+                        technique: Technique::Claiming,
+                        units: vec![line, box_unit],
+                        cells,
+                        digits: vec![value],
+                        placement: None,
+                        eliminations,
+                    });
+                }
+            }
+        }
+    }
+    None
+}
+
+/// A row, column or box holds 1 to 9, adding up to 45 on a classic board.
+/// Taking the cages wholly inside it from that total leaves the sum of the
+/// other cells (innies); taking the total from the cages touching it leaves
+/// the sum of their cells outside (outies). One empty cell left over must
+/// hold what remains.
+fn find_rule_of_45(board: &SudokuBoard) -> Option<Step> {
+    if board.cages.is_empty() {
+        return None;
+    }
+    let size = board.size();
+    let total = (size * (size + 1) / 2) as i32;
+    for unit in Unit::all(board).into_iter().take(3 * size) {
+        let cells = unit.cells(board);
+        let touching: Vec<usize> = (0..board.cages.len())
+            .filter(|cage| {
+                board.cages[*cage]
+                    .cells
+                    .iter()
+                    .any(|cell| unit.contains(board, *cell))
+            })
+            .collect();
+        let (inside, overhanging): (Vec<usize>, Vec<usize>) = touching.iter().partition(|cage| {
+            board.cages[**cage]
+                .cells
+                .iter()
+                .all(|cell| unit.contains(board, *cell))
+        });
+
+        let innies: Vec<(usize, usize)> = cells
+            .iter()
+            .filter(|cell| {
+                !inside
+                    .iter()
+                    .any(|cage| board.cages[*cage].cells.contains(cell))
+            })
+            .copied()
+            .collect();
+        let innie_sum = total
+            - inside
+                .iter()
+                .map(|cage| board.cages[*cage].sum)
+                .sum::<i32>();
+        if let Some(step) = remaining_cell_step(board, unit, &innies, innie_sum) {
+            return Some(step);
+        }
+
+        let covered = cells.iter().all(|cell| {
+            touching
+                .iter()
+                .any(|cage| board.cages[*cage].cells.contains(cell))
+        });
+        if !covered || overhanging.is_empty() {
+            continue;
+        }
+        let outies: Vec<(usize, usize)> = overhanging
+            .iter()
+            .flat_map(|cage| board.cages[*cage].cells.clone())
+            .filter(|cell| !unit.contains(board, *cell))
+            .collect();
+        let outie_sum = touching
+            .iter()
+            .map(|cage| board.cages[*cage].sum)
+            .sum::<i32>()
+            - total;
+        if let Some(step) = remaining_cell_step(board, unit, &outies, outie_sum) {
+            return Some(step);
+        }
+    }
+    None
+}
+
+/// Places `sum` less the filled cells of `cells` when exactly one of them is
+/// empty and can hold it.
+fn remaining_cell_step(
+    board: &SudokuBoard,
+    unit: Unit,
+    cells: &[(usize, usize)],
+    sum: i32,
+) -> Option<Step> {
+    let empty: Vec<(usize, usize)> = cells
+        .iter()
+        .filter(|(r, c)| board.puzzle[*r][*c] == 0)
+        .copied()
+        .collect();
+    if empty.len() != 1 {
+        return None;
+    }
+    let (row, col) = empty[0];
+    let value = sum - cells.iter().map(|(r, c)| board.puzzle[*r][*c]).sum::<i32>();
+    if !board.possible_solutions[row][col].contains(&value) {
+        return None;
+    }
+    Some(Step {
+        technique: Technique::RuleOf45,
+        units: vec![unit],
+        cells: cells.to_vec(),
+        digits: vec![value],
+        placement: Some((row, col, value)),
+        eliminations: Vec::new(),
+    })
+}
+
+/// Candidates of a cage's empty cells that appear in no combination of
+/// digits the cells can still take together.
+fn find_cage_combination(board: &SudokuBoard) -> Option<Step> {
+    for cage in 0..board.cages.len() {
+        let cells: Vec<(usize, usize)> = board.cages[cage]
+            .cells
+            .iter()
+            .filter(|(r, c)| board.puzzle[*r][*c] == 0)
+            .copied()
+            .collect();
+        if cells.is_empty() {
+            continue;
+        }
+        let placed: Vec<i32> = board.cages[cage]
+            .cells
+            .iter()
+            .map(|(r, c)| board.puzzle[*r][*c])
+            .filter(|value| *value != 0)
+            .collect();
+        let candidates: Vec<Vec<i32>> = cells
+            .iter()
+            .map(|(r, c)| board.possible_solutions[*r][*c].clone())
+            .collect();
+        let mut digits: Vec<i32> = board.cages[cage]
+            .combinations(board.size())
+            .into_iter()
+            .filter(|combination| placed.iter().all(|value| combination.contains(value)))
+            .map(|combination| {
+                combination
+                    .into_iter()
+                    .filter(|value| !placed.contains(value))
+                    .collect::<Vec<i32>>()
+            })
+            .filter(|remaining| can_fill(&candidates, remaining))
+            .flatten()
+            .collect();
+        digits.sort_unstable();
+        digits.dedup();
+        let eliminations: Vec<(usize, usize, i32)> = cells
+            .iter()
+            .flat_map(|(r, c)| {
+                board.possible_solutions[*r][*c]
+                    .iter()
+                    .filter(|value| !digits.contains(value))
+                    .map(move |value| (*r, *c, *value))
+            })
+            .collect();
+        if !eliminations.is_empty() {
+            return Some(Step {
+                technique: Technique::CageCombination,
+                units: vec![Unit::Cage(cage)],
+                cells,
+                digits,
+                placement: None,
+                eliminations,
+            });
+        }
+    }
+    None
+}
+
+/// Candidates of one cell of a pair constraint that no candidate of the
+/// other cell fits with.
+fn find_pair_elimination(board: &SudokuBoard) -> Option<Step> {
+    for (index, pair) in board.pair_constraints.iter().enumerate() {
+        for (cell, other) in [(pair.a, pair.b), (pair.b, pair.a)] {
+            if board.puzzle[cell.0][cell.1] != 0 {
+                continue;
+            }
+            let partners: Vec<i32> = match board.puzzle[other.0][other.1] {
+                0 => board.possible_solutions[other.0][other.1].clone(),
+                value => vec![value],
+            };
+            let eliminations: Vec<(usize, usize, i32)> = board.possible_solutions[cell.0][cell.1]
+                .iter()
+                .filter(|value| {
+                    !partners
+                        .iter()
+                        .any(|partner| pair.relation.allows(**value, *partner))
+                })
+                .map(|value| (cell.0, cell.1, *value))
+                .collect();
+            if !eliminations.is_empty() {
+                return Some(Step {
+                    technique: Technique::PairElimination,
+                    units: vec![Unit::Pair(index, pair.relation)],
+                    cells: vec![cell, other],
+                    digits: eliminations.iter().map(|e| e.2).collect(),
+                    placement: None,
+                    eliminations,
+                });
+            }
+        }
+    }
+    None
+}
+
+/// Candidates on a line that no way of filling the whole line uses. Cells
+/// of the line that see each other must differ. Lines with more than
+/// `FILLING_LIMIT` fillings are skipped until other steps narrow them down.
+fn find_line_elimination(board: &SudokuBoard) -> Option<Step> {
+    for (index, line) in board.lines.iter().enumerate() {
+        if count_fillings(board, &line.cells) > FILLING_LIMIT {
+            continue;
+        }
+        let mut used: Vec<Vec<bool>> = line
+            .cells
+            .iter()
+            .map(|_| vec![false; board.size() + 1])
+            .collect();
+        let mut values = SudokuBoard::get_line_values(board, line);
+        let allows = |values: &[i32]| line.allows(values, board.size());
+        mark_fillings(board, &line.cells, &allows, 0, &mut values, &mut used);
+        let eliminations: Vec<(usize, usize, i32)> = line
+            .cells
+            .iter()
+            .enumerate()
+            .filter(|(_, (r, c))| board.puzzle[*r][*c] == 0)
+            .flat_map(|(position, (r, c))| {
+                board.possible_solutions[*r][*c]
+                    .iter()
+                    .filter(|value| !used[position][**value as usize])
+                    .map(|value| (*r, *c, *value))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        if !eliminations.is_empty() {
+            let mut digits: Vec<i32> = eliminations.iter().map(|e| e.2).collect();
+            digits.sort_unstable();
+            digits.dedup();
+            return Some(Step {
+                technique: Technique::LineElimination,
+                units: vec![Unit::Line(index, line.kind)],
+                cells: line.cells.clone(),
+                digits,
+                placement: None,
+                eliminations,
+            });
+        }
+    }
+    None
+}
+
+/// Tries every filling of the empty `cells` from `position` on from their
+/// candidates, marking in `used` the digits of each complete filling that
+/// `allows` accepts. Cells that see each other must differ.
+fn mark_fillings(
+    board: &SudokuBoard,
+    cells: &[(usize, usize)],
+    allows: &dyn Fn(&[i32]) -> bool,
+    position: usize,
+    values: &mut Vec<i32>,
+    used: &mut Vec<Vec<bool>>,
+) {
+    if position == values.len() {
+        for (position, value) in values.iter().enumerate() {
+            used[position][*value as usize] = true;
+        }
+        return;
+    }
+    let (row, col) = cells[position];
+    if board.puzzle[row][col] != 0 {
+        mark_fillings(board, cells, allows, position + 1, values, used);
+        return;
+    }
+    for value in board.possible_solutions[row][col].clone() {
+        let clashes = (0..position)
+            .any(|other| values[other] == value && sees(board, cells[other], (row, col)));
+        if clashes {
+            continue;
+        }
+        values[position] = value;
+        if allows(values) {
+            mark_fillings(board, cells, allows, position + 1, values, used);
+        }
+        values[position] = 0;
+    }
+}
+
+/// Above this many ways of filling a line's or clue's empty cells from their
+/// candidates, `find_line_elimination` skips the line and
+/// `find_clue_elimination` only checks each candidate alone.
+const FILLING_LIMIT: usize = 20_000;
+
+/// The product of the candidate counts of the empty `cells`, saturating.
+fn count_fillings(board: &SudokuBoard, cells: &[(usize, usize)]) -> usize {
+    cells
+        .iter()
+        .filter(|(r, c)| board.puzzle[*r][*c] == 0)
+        .fold(1usize, |fillings, (r, c)| {
+            fillings.saturating_mul(board.possible_solutions[*r][*c].len())
+        })
+}
+
+/// Candidates along an outside clue that no way of meeting it uses.
+/// Sandwiches are worked out from the digit sets that fit between the 1 and
+/// the highest digit; other clues try every filling of their cells, or each
+/// candidate alone when there are too many fillings.
+fn find_clue_elimination(board: &SudokuBoard) -> Option<Step> {
+    let size = board.size();
+    for (index, clue) in board.outside_clues.iter().enumerate() {
+        let cells = clue.cells(size);
+        let mut values = SudokuBoard::get_clue_values(board, clue);
+        let mut used: Vec<Vec<bool>> = cells.iter().map(|_| vec![false; size + 1]).collect();
+        let fillings = count_fillings(board, &cells);
+        if clue.kind == ClueKind::Sandwich {
+            mark_sandwiches(board, clue, &cells, &mut used);
+        } else if fillings <= FILLING_LIMIT {
+            let allows = |values: &[i32]| clue.allows(values, size);
+            mark_fillings(board, &cells, &allows, 0, &mut values, &mut used);
+        } else {
+            for (position, (r, c)) in cells.iter().enumerate() {
+                let mut values = values.clone();
+                for value in &board.possible_solutions[*r][*c] {
+                    values[position] = *value;
+                    used[position][*value as usize] = clue.allows(&values, size);
+                }
+            }
+        }
+        let eliminations: Vec<(usize, usize, i32)> = cells
+            .iter()
+            .enumerate()
+            .filter(|(_, (r, c))| board.puzzle[*r][*c] == 0)
+            .flat_map(|(position, (r, c))| {
+                board.possible_solutions[*r][*c]
+                    .iter()
+                    .filter(|value| !used[position][**value as usize])
+                    .map(|value| (*r, *c, *value))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        if !eliminations.is_empty() {
+            let mut digits: Vec<i32> = eliminations.iter().map(|e| e.2).collect();
+            digits.sort_unstable();
+            digits.dedup();
+            return Some(Step {
+                technique: Technique::ClueElimination,
+                units: vec![Unit::Clue(index, clue.kind)],
+                cells,
+                digits,
+                placement: None,
+                eliminations,
+            });
+        }
+    }
+    None
+}
+
+/// Marks in `used` the digits a sandwich row or column can hold: for each
+/// placing of the 1 and the highest digit, each set of digits between them
+/// adding up to the clue, if the cells between and outside can take those
+/// digits and the rest.
+fn mark_sandwiches(
+    board: &SudokuBoard,
+    clue: &OutsideClue,
+    cells: &[(usize, usize)],
+    used: &mut [Vec<bool>],
+) {
+    let size = board.size() as i32;
+    let candidates: Vec<Vec<i32>> = cells
+        .iter()
+        .map(|(r, c)| match board.puzzle[*r][*c] {
+            0 => board.possible_solutions[*r][*c].clone(),
+            value => vec![value],
+        })
+        .collect();
+    for low in 0..cells.len() {
+        for high in 0..cells.len() {
+            if low == high || !candidates[low].contains(&1) || !candidates[high].contains(&size) {
+                continue;
+            }
+            let (first, last) = (low.min(high), low.max(high));
+            let inside: Vec<usize> = (first + 1..last).collect();
+            let outside: Vec<usize> = (0..cells.len())
+                .filter(|position| *position < first || *position > last)
+                .collect();
+            for digits in cages::combinations(clue.value, inside.len(), board.size()) {
+                if digits.contains(&1) || digits.contains(&size) {
+                    continue;
+                }
+                let rest: Vec<i32> = (2..size).filter(|value| !digits.contains(value)).collect();
+                let inside_candidates: Vec<Vec<i32>> =
+                    inside.iter().map(|p| candidates[*p].clone()).collect();
+                let outside_candidates: Vec<Vec<i32>> =
+                    outside.iter().map(|p| candidates[*p].clone()).collect();
+                if !can_fill(&inside_candidates, &digits) || !can_fill(&outside_candidates, &rest) {
+                    continue;
+                }
+                used[low][1] = true;
+                used[high][size as usize] = true;
+                for (positions, digits) in [(&inside, &digits), (&outside, &rest)] {
+                    for position in positions {
+                        for value in &candidates[*position] {
+                            if digits.contains(value) {
+                                used[*position][*value as usize] = true;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Whether each cell can take a different one of `digits` from its
+/// candidates, using every digit.
+fn can_fill(candidates: &[Vec<i32>], digits: &[i32]) -> bool {
+    match candidates.split_first() {
+        None => digits.is_empty(),
+        Some((first, rest)) => digits.iter().enumerate().any(|(index, digit)| {
+            first.contains(digit) && {
+                let mut others = digits.to_vec();
+                others.remove(index);
+                can_fill(rest, &others)
+            }
+        }),
+    }
+}
+
+fn find_naked_subset(board: &SudokuBoard, size: usize) -> Option<Step> {
+    let technique = if size == 2 {
+        Technique::NakedPair
+    } else {
+        Technique::NakedTriple
+    };
+    for unit in Unit::all(board) {
+        let open_cells: Vec<(usize, usize)> = unit
+            .cells(board)
+            .into_iter()
+            .filter(|(r, c)| {
+                let count = board.possible_solutions[*r][*c].len();
+                board.puzzle[*r][*c] == 0 && count >= 2 && count <= size
+            })
+            .collect();
+        for subset in combinations(&open_cells, size) {
+            let mut digits: Vec<i32> = subset
+                .iter()
+                .flat_map(|(r, c)| board.possible_solutions[*r][*c].clone())
+                .collect();
+            digits.sort_unstable();
+            digits.dedup();
+            if digits.len() != size {
+                continue;
+            }
+            let mut eliminations = Vec::new();
+            for (r, c) in unit.cells(board) {
+                if subset.contains(&(r, c)) {
+                    continue;
+                }
+                for value in &digits {
+                    if board.possible_solutions[r][c].contains(value) {
+                        eliminations.push((r, c, *value));
+                    }
+                }
+            }
+            if !eliminations.is_empty() {
+                return Some(Step {
+                    technique,
+                    units: vec![unit],
+                    cells: subset,
+                    digits,
+                    placement: None,
+                    eliminations,
+                });
+            }
+        }
+    }
+    None
+}
+
+fn find_hidden_subset(board: &SudokuBoard, size: usize) -> Option<Step> {
+    let technique = if size == 2 {
+        Technique::HiddenPair
+    } else {
+        Technique::HiddenTriple
+    };
+    for unit in Unit::all(board)
+        .into_iter()
+        .filter(|unit| unit.is_complete(board))
+    {
+        let open_digits: Vec<i32> = (1..=board.size() as i32)
+            .filter(|value| {
+                let count = candidate_cells(board, &unit, *value).len();
+                count >= 2 && count <= size
+            })
+            .collect();
+        for digits in combinations(&open_digits, size) {
+            let mut cells: Vec<(usize, usize)> = digits
+                .iter()
+                .flat_map(|value| candidate_cells(board, &unit, *value))
+                .collect();
+            cells.sort_unstable();
+            cells.dedup();
+            if cells.len() != size {
+                continue;
+            }
+            let mut eliminations = Vec::new();
+            for (r, c) in &cells {
+                for value in &board.possible_solutions[*r][*c] {
+                    if !digits.contains(value) {
+                        eliminations.push((*r, *c, *value));
+                    }
+                }
+            }
+            if !eliminations.is_empty() {
+                return Some(Step {
+                    technique,
+                    units: vec![unit],
+                    cells,
+                    digits,
+                    placement: None,
+                    eliminations,
+                });
+            }
+        }
+    }
+    None
+}
+
+/// X-Wing (size 2) and Swordfish (size 3), with rows or columns as the base sets.
+fn find_fish(board: &SudokuBoard, size: usize) -> Option<Step> {
+    let technique = if size == 2 {
+        Technique::XWing
+    } else {
+        Technique::Swordfish
+    };
+    for value in 1..=board.size() as i32 {
+        for by_rows in [true, false] {
+            let line = |i: usize| if by_rows { Unit::Row(i) } else { Unit::Col(i) };
+            let cover_line = |i: usize| if by_rows { Unit::Col(i) } else { Unit::Row(i) };
+            let cross_index = |cell: (usize, usize)| if by_rows { cell.1 } else { cell.0 };
+
+            let base_lines: Vec<usize> = (0..board.size())
+                .filter(|i| {
+                    let count = candidate_cells(board, &line(*i), value).len();
+                    count >= 2 && count <= size
+                })
+                .collect();
+            for bases in combinations(&base_lines, size) {
+                let mut cells = Vec::new();
+                for base in &bases {
+                    cells.extend(candidate_cells(board, &line(*base), value));
+                }
+                let mut covers: Vec<usize> = cells.iter().map(|cell| cross_index(*cell)).collect();
+                covers.sort_unstable();
+                covers.dedup();
+                if covers.len() != size {
+                    continue;
+                }
+                let mut eliminations = Vec::new();
+                for cover in &covers {
+                    for cell in candidate_cells(board, &cover_line(*cover), value) {
+                        if !cells.contains(&cell) {
+                            eliminations.push((cell.0, cell.1, value));
+                        }
+                    }
+                }
+                if !eliminations.is_empty() {
+                    let mut units: Vec<Unit> = bases.iter().map(|i| line(*i)).collect();
+                    units.extend(covers.iter().map(|i| cover_line(*i)));
+                    return Some(Step {
+                        technique,
+                        units,
+                        cells,
+                        digits: vec![value],
+                        placement: None,
+                        eliminations,
+                    });
+                }
+            }
+        }
+    }
+    None
+}
+
+/// A bivalue pivot {x, y} seeing pincers {x, z} and {y, z}: z goes in one of
+/// the pincers, so it can be removed from every cell seeing both.
+fn find_xy_wing(board: &SudokuBoard) -> Option<Step> {
+    let size = board.size();
+    let bivalue: Vec<(usize, usize)> = (0..size * size)
+        .map(|i| (i / size, i % size))
+        .filter(|(r, c)| board.puzzle[*r][*c] == 0 && board.possible_solutions[*r][*c].len() == 2)
+        .collect();
+    for pivot in &bivalue {
+        let pivot_values = &board.possible_solutions[pivot.0][pivot.1];
+        let (x, y) = (pivot_values[0], pivot_values[1]);
+        let wings: Vec<&(usize, usize)> = bivalue
+            .iter()
+            .filter(|cell| sees(board, *pivot, **cell))
+            .collect();
+        for first in &wings {
+            let first_values = &board.possible_solutions[first.0][first.1];
+            if !first_values.contains(&x) || first_values.contains(&y) {
+                continue;
+            }
+            let z = if first_values[0] == x {
+                first_values[1]
+            } else {
+                first_values[0]
+            };
+            for second in &wings {
+                let second_values = &board.possible_solutions[second.0][second.1];
+                if !(second_values.contains(&y) && second_values.contains(&z)) {
+                    continue;
+                }
+                let mut eliminations = Vec::new();
+                for i in 0..size {
+                    for j in 0..size {
+                        if (i, j) != *pivot
+                            && sees(board, **first, (i, j))
+                            && sees(board, **second, (i, j))
+                            && board.possible_solutions[i][j].contains(&z)
+                        {
+                            eliminations.push((i, j, z));
+                        }
+                    }
+                }
+                if !eliminations.is_empty() {
+                    return Some(Step {
+                        technique: Technique::XYWing,
+                        units: Vec::new(),
+                        cells: vec![*pivot, **first, **second],
+                        digits: vec![x, y, z],
+                        placement: None,
+                        eliminations,
+                    });
+                }
+            }
+        }
+    }
+    None
+}
+
+fn combinations<T: Clone>(items: &[T], size: usize) -> Vec<Vec<T>> {
+    if size == 0 {
+        return vec![Vec::new()];
+    }
+    let mut ret_value = Vec::new();
+    for i in 0..items.len() {
+        for mut rest in combinations(&items[i + 1..], size - 1) {
+            rest.insert(0, items[i].clone());
+            ret_value.push(rest);
+        }
+    }
+    ret_value
+}
+
+#[test]
+fn logic_solves_easy() {
+    let s_board = SudokuBoard::from_puzzle([
+        [0, 6, 0, 0, 3, 0, 8, 7, 0],
+        [0, 0, 0, 2, 0, 0, 1, 4, 3],
+        [0, 1, 7, 0, 5, 8, 0, 0, 0],
+        [0, 7, 0, 0, 0, 1, 0, 2, 8],
+        [9, 5, 4, 0, 8, 0, 0, 0, 0],
+        [8, 0, 0, 6, 0, 7, 3, 0, 4],
+        [0, 4, 0, 9, 0, 0, 2, 8, 1],
+        [0, 0, 9, 0, 1, 4, 0, 0, 7],
+        [1, 0, 6, 7, 0, 0, 4, 0, 0],
+    ]);
+    let path = solve_path(&s_board);
+    assert!(path.solved());
+    assert!(path.steps.iter().all(|step| step.placement.is_some()));
+}
+
+#[test]
+fn finds_x_wing() {
+    // Digit 1 is confined to columns 1 and 9 in rows 1 and 9.
+    let mut board = SudokuBoard::new();
+    for i in 0..9 {
+        for j in 0..9 {
+            board.possible_solutions[i][j] = vec![2, 3];
+        }
+    }
+    for row in [0, 8] {
+        board.possible_solutions[row][0].push(1);
+        board.possible_solutions[row][8].push(1);
+    }
+    board.possible_solutions[4][0].push(1);
+    board.possible_solutions[4][4].push(1);
+
+    let step = find_step(&board, Technique::XWing).unwrap();
+    assert_eq!(
+        step.units,
+        vec![Unit::Row(0), Unit::Row(8), Unit::Col(0), Unit::Col(8)]
+    );
+    assert_eq!(step.eliminations, vec![(4, 0, 1)]);
+}
+
+#[test]
+fn logic_solves_jigsaw() {
+    use crate::regions::Regions;
+
+    let regions = Regions::parse(
+        "112222333 111222333 111122333 444555566 444555666 444556666 777888999 777888999 777888999",
+    )
+    .unwrap();
+    let board = SudokuBoard::from_jigsaw(
+        SudokuBoard::from_puzzle_string(
+            ".36.17.25.19.45.63.25.39.74.53.98.17.71.63.89.84.72.36.62.54.98.48.26.51.97.81.42",
+        )
+        .unwrap()
+        .puzzle,
+        regions,
+    );
+    assert_eq!(Unit::Box(0).cells(&board)[2], (1, 0));
+    assert!(Unit::Box(1).contains(&board, (0, 2)));
+    let path = solve_path(&board);
+    assert!(path.solved());
+}
+
+#[test]
+fn skips_lines_with_too_many_fillings() {
+    use crate::lines::Line;
+
+    let mut long = SudokuBoard::new();
+    let row: Vec<(usize, usize)> = (0..9).map(|col| (0, col)).collect();
+    SudokuBoard::add_lines(&mut long, vec![Line::new(LineKind::Whisper, row)]).unwrap();
+    assert!(long.possible_solutions[0][0].contains(&5));
+    assert!(find_step(&long, Technique::LineElimination).is_none());
+
+    let mut short = SudokuBoard::new();
+    let cells = vec![(0, 0), (0, 1), (0, 2)];
+    SudokuBoard::add_lines(&mut short, vec![Line::new(LineKind::Whisper, cells)]).unwrap();
+    let step = find_step(&short, Technique::LineElimination).unwrap();
+    // The middle cell needs two different neighbours at least 5 away.
+    assert_eq!(
+        step.eliminations,
+        vec![(0, 0, 5), (0, 1, 4), (0, 1, 5), (0, 1, 6), (0, 2, 5)]
+    );
+}
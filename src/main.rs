@@ -1,6 +1,14 @@
-mod sudoku;
+use std::{env, fs, process};
+use sudoku_solver::{explain, fpuzzles, logic, rating, sudoku, variant};
 
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match &args[..] {
+        [flag, path] if flag == "--import" => return solve_file(path, true),
+        [path] => return solve_file(path, false),
+        _ => {}
+    }
+
     let mut s_board = sudoku::SudokuBoard::from_puzzle([
         [0, 3, 0, 8, 0, 7, 0, 0, 5],
         [0, 0, 0, 0, 0, 5, 0, 0, 3],
@@ -14,6 +22,16 @@ fn main() {
     ]);
     sudoku::SudokuBoard::print_puzzle(&s_board.puzzle);
 
+    for sentence in explain::explain_path(&logic::solve_path(&s_board)) {
+        println!("{}", sentence);
+    }
+
+    let puzzle_rating = rating::rate(&s_board);
+    println!(
+        "\nRated {} ({:.1}, {} steps, {} guesses)",
+        puzzle_rating.difficulty, puzzle_rating.score, puzzle_rating.steps, puzzle_rating.guesses
+    );
+
     sudoku::SudokuBoard::solve_deterministic(&mut s_board);
 
     if sudoku::SudokuBoard::populated(&s_board) && sudoku::SudokuBoard::validate_board(&s_board) {
@@ -31,3 +49,38 @@ fn main() {
         }
     }
 }
+
+/// Loads a variant description (see `variant::load_variant`), or with
+/// `--import` an f-puzzles export, then explains and solves it.
+fn solve_file(path: &str, import: bool) {
+    let board = fs::read_to_string(path)
+        .map_err(|error| error.to_string())
+        .and_then(|text| {
+            if import {
+                fpuzzles::import_fpuzzles(&text).map_err(|error| error.to_string())
+            } else {
+                variant::load_variant(&text).map_err(|error| error.to_string())
+            }
+        })
+        .unwrap_or_else(|error| {
+            eprintln!("{}: {}", path, error);
+            process::exit(1);
+        });
+    sudoku::SudokuBoard::print_board(&board);
+
+    for sentence in explain::explain_path(&logic::solve_path(&board)) {
+        println!("{}", sentence);
+    }
+
+    match sudoku::SudokuBoard::count_solutions(&board, 2) {
+        0 => println!("\nNo solution"),
+        count => {
+            if count > 1 {
+                println!("\nMore than one solution; showing the first");
+            }
+            let solved = sudoku::SudokuBoard::solve_backtracking(&board).unwrap();
+            println!("\n\nCompleted\n");
+            sudoku::SudokuBoard::print_board(&solved);
+        }
+    }
+}
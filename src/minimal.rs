@@ -0,0 +1,122 @@
+use crate::sudoku::{Grid, SudokuBoard};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Minimised {
+    pub puzzle: Grid,
+    /// Givens removed from the original, as (row, col, value).
+    pub redundant: Vec<(usize, usize, i32)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MinimiseError {
+    NoSolution,
+    MultipleSolutions,
+}
+
+impl fmt::Display for MinimiseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MinimiseError::NoSolution => write!(f, "puzzle has no solution"),
+            MinimiseError::MultipleSolutions => write!(f, "puzzle has more than one solution"),
+        }
+    }
+}
+
+impl std::error::Error for MinimiseError {}
+
+fn check_unique(board: &SudokuBoard) -> Result<(), MinimiseError> {
+    match SudokuBoard::count_solutions(board, 2) {
+        0 => Err(MinimiseError::NoSolution),
+        1 => Ok(()),
+        _ => Err(MinimiseError::MultipleSolutions),
+    }
+}
+
+/// Removes givens in row-major order while the puzzle stays unique. A given
+/// that is needed stays needed as others are removed, so one pass leaves a
+/// puzzle where every given is necessary.
+pub fn minimise(board: &SudokuBoard) -> Result<Minimised, MinimiseError> {
+    check_unique(board)?;
+    let mut minimal = board.clone();
+    let mut redundant = Vec::new();
+    for i in 0..board.size() {
+        for j in 0..board.size() {
+            let value = minimal.puzzle[i][j];
+            if value == 0 {
+                continue;
+            }
+            minimal.puzzle[i][j] = 0;
+            if SudokuBoard::count_solutions(&minimal, 2) == 1 {
+                redundant.push((i, j, value));
+            } else {
+                minimal.puzzle[i][j] = value;
+            }
+        }
+    }
+    Ok(Minimised {
+        puzzle: minimal.puzzle,
+        redundant,
+    })
+}
+
+/// Givens that could each be removed on their own without losing uniqueness.
+/// Removing one may make another necessary, so `minimise` can keep some of these.
+pub fn redundant_givens(board: &SudokuBoard) -> Result<Vec<(usize, usize, i32)>, MinimiseError> {
+    check_unique(board)?;
+    let mut working = board.clone();
+    let mut redundant = Vec::new();
+    for i in 0..board.size() {
+        for j in 0..board.size() {
+            let value = working.puzzle[i][j];
+            if value == 0 {
+                continue;
+            }
+            working.puzzle[i][j] = 0;
+            if SudokuBoard::count_solutions(&working, 2) == 1 {
+                redundant.push((i, j, value));
+            }
+            working.puzzle[i][j] = value;
+        }
+    }
+    Ok(redundant)
+}
+
+pub fn is_minimal(board: &SudokuBoard) -> Result<bool, MinimiseError> {
+    Ok(redundant_givens(board)?.is_empty())
+}
+
+#[test]
+fn minimises_solved_grid() {
+    let solution = SudokuBoard::from_puzzle([
+        [4, 3, 6, 8, 1, 7, 9, 2, 5],
+        [7, 1, 9, 2, 4, 5, 8, 6, 3],
+        [8, 2, 5, 6, 3, 9, 1, 7, 4],
+        [6, 5, 3, 4, 9, 8, 2, 1, 7],
+        [2, 7, 1, 5, 6, 3, 4, 8, 9],
+        [9, 8, 4, 1, 7, 2, 5, 3, 6],
+        [1, 6, 2, 7, 5, 4, 3, 9, 8],
+        [3, 4, 8, 9, 2, 6, 7, 5, 1],
+        [5, 9, 7, 3, 8, 1, 6, 4, 2],
+    ]);
+    let minimised = minimise(&solution).unwrap();
+    let givens = minimised
+        .puzzle
+        .iter()
+        .flatten()
+        .filter(|v| **v != 0)
+        .count();
+    assert_eq!(givens + minimised.redundant.len(), 81);
+    let minimal = SudokuBoard::from_grid(minimised.puzzle, solution.box_shape.unwrap());
+    assert_eq!(is_minimal(&minimal), Ok(true));
+    assert_eq!(is_minimal(&solution), Ok(false));
+    assert_eq!(SudokuBoard::find_solutions(&minimal, 1)[0], solution.puzzle);
+}
+
+#[test]
+fn rejects_ambiguous_puzzle() {
+    assert_eq!(
+        minimise(&SudokuBoard::new()),
+        Err(MinimiseError::MultipleSolutions)
+    );
+}
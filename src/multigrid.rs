@@ -0,0 +1,574 @@
+use crate::explain::parse_cell_name;
+use crate::sudoku::{BoxShape, Grid, SudokuBoard, MAX_SIZE};
+use crate::symbols::SymbolSet;
+use std::fmt;
+
+/// Cell values on the canvas of a multi-grid puzzle, row-major with 0 for
+/// an empty cell or one outside every grid. Rectangular rather than square.
+pub type Canvas = Vec<Vec<i32>>;
+
+/// Several grids placed on a larger canvas, sharing the cells where they
+/// overlap, such as the five grids of Samurai. Every grid has boxes of
+/// `box_shape` and overlapping grids share whole boxes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiGrid {
+    pub box_shape: BoxShape,
+    /// The canvas cell at the top-left corner of each grid.
+    pub placements: Vec<(usize, usize)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MultiGridError {
+    NoGrids,
+    /// Grids larger than `MAX_SIZE`.
+    TooLarge {
+        size: usize,
+    },
+    /// A grid overlaps another without sharing whole boxes.
+    Misaligned {
+        grid: usize,
+    },
+    /// A header line that is neither `size` nor `grid`.
+    BadHeader {
+        line: String,
+    },
+    WrongRowCount {
+        expected: usize,
+        found: usize,
+    },
+    /// A character that is not a symbol, `.` or a space.
+    UnknownSymbol {
+        row: usize,
+        col: usize,
+        symbol: char,
+    },
+    /// A digit or `.` outside every grid, or a space inside one.
+    WrongCell {
+        row: usize,
+        col: usize,
+    },
+}
+
+impl fmt::Display for MultiGridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MultiGridError::NoGrids => write!(f, "puzzle has no grids"),
+            MultiGridError::TooLarge { size } => {
+                write!(f, "grids of size {} are larger than {}", size, MAX_SIZE)
+            }
+            MultiGridError::Misaligned { grid } => {
+                write!(
+                    f,
+                    "grid {} overlaps another without sharing boxes",
+                    grid + 1
+                )
+            }
+            MultiGridError::BadHeader { line } => write!(f, "cannot read '{}'", line),
+            MultiGridError::WrongRowCount { expected, found } => {
+                write!(f, "expected {} canvas rows but found {}", expected, found)
+            }
+            MultiGridError::UnknownSymbol { row, col, symbol } => {
+                write!(
+                    f,
+                    "'{}' at r{}c{} is not a symbol",
+                    symbol,
+                    row + 1,
+                    col + 1
+                )
+            }
+            MultiGridError::WrongCell { row, col } => {
+                write!(f, "r{}c{} does not match the grids", row + 1, col + 1)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MultiGridError {}
+
+impl MultiGrid {
+    /// Checks that there is at least one grid, that grids are no larger
+    /// than `MAX_SIZE` and that overlapping grids share whole boxes.
+    pub fn new(
+        box_shape: BoxShape,
+        placements: Vec<(usize, usize)>,
+    ) -> Result<MultiGrid, MultiGridError> {
+        if placements.is_empty() {
+            return Err(MultiGridError::NoGrids);
+        }
+        let size = box_shape.size();
+        if size > MAX_SIZE {
+            return Err(MultiGridError::TooLarge { size });
+        }
+        for (grid, (row, col)) in placements.iter().enumerate() {
+            let misaligned = placements[..grid].iter().any(|(r, c)| {
+                let overlaps = row.abs_diff(*r) < size && col.abs_diff(*c) < size;
+                overlaps
+                    && (!row.abs_diff(*r).is_multiple_of(box_shape.rows)
+                        || !col.abs_diff(*c).is_multiple_of(box_shape.cols))
+            });
+            if misaligned {
+                return Err(MultiGridError::Misaligned { grid });
+            }
+        }
+        Ok(MultiGrid {
+            box_shape,
+            placements,
+        })
+    }
+
+    /// Five classic grids: four corners sharing a box each with the centre.
+    pub fn samurai() -> MultiGrid {
+        MultiGrid {
+            box_shape: BoxShape::CLASSIC,
+            placements: vec![(0, 0), (0, 12), (6, 6), (12, 0), (12, 12)],
+        }
+    }
+
+    /// Four classic grids on a 12x12 canvas, each sharing four boxes with
+    /// each neighbour.
+    pub fn butterfly() -> MultiGrid {
+        MultiGrid {
+            box_shape: BoxShape::CLASSIC,
+            placements: vec![(0, 0), (0, 3), (3, 0), (3, 3)],
+        }
+    }
+
+    /// Two classic grids sharing a corner box.
+    pub fn twodoku() -> MultiGrid {
+        MultiGrid {
+            box_shape: BoxShape::CLASSIC,
+            placements: vec![(0, 0), (6, 6)],
+        }
+    }
+
+    /// The side of each grid.
+    pub fn size(&self) -> usize {
+        self.box_shape.size()
+    }
+
+    pub fn height(&self) -> usize {
+        self.placements.iter().map(|(row, _)| row).max().unwrap() + self.size()
+    }
+
+    pub fn width(&self) -> usize {
+        self.placements.iter().map(|(_, col)| col).max().unwrap() + self.size()
+    }
+
+    /// An empty canvas.
+    pub fn empty(&self) -> Canvas {
+        vec![vec![0; self.width()]; self.height()]
+    }
+
+    /// The grids covering canvas cell (row, col).
+    pub fn grids_at(&self, row: usize, col: usize) -> Vec<usize> {
+        let size = self.size();
+        (0..self.placements.len())
+            .filter(|grid| {
+                let (top, left) = self.placements[*grid];
+                (top..top + size).contains(&row) && (left..left + size).contains(&col)
+            })
+            .collect()
+    }
+
+    pub fn contains(&self, row: usize, col: usize) -> bool {
+        !self.grids_at(row, col).is_empty()
+    }
+
+    /// One grid of the canvas as a board of its own.
+    pub fn board(&self, canvas: &Canvas, grid: usize) -> SudokuBoard {
+        let (top, left) = self.placements[grid];
+        let size = self.size();
+        let puzzle: Grid = canvas[top..top + size]
+            .iter()
+            .map(|row| row[left..left + size].to_vec())
+            .collect();
+        SudokuBoard::from_grid(puzzle, self.box_shape)
+    }
+
+    /// The candidates of every empty canvas cell: those left by every grid
+    /// the cell is in.
+    pub fn candidates(&self, canvas: &Canvas) -> Vec<Vec<Vec<i32>>> {
+        let mut candidates: Vec<Vec<Vec<i32>>> = canvas
+            .iter()
+            .enumerate()
+            .map(|(row, values)| {
+                (0..values.len())
+                    .map(|col| {
+                        if self.contains(row, col) && values[col] == 0 {
+                            (1..=self.size() as i32).collect()
+                        } else {
+                            Vec::new()
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        for grid in 0..self.placements.len() {
+            let (top, left) = self.placements[grid];
+            let board = self.board(canvas, grid);
+            for (i, row) in board.possible_solutions.iter().enumerate() {
+                for (j, values) in row.iter().enumerate() {
+                    candidates[top + i][left + j].retain(|value| values.contains(value));
+                }
+            }
+        }
+        candidates
+    }
+
+    /// Whether every grid is full and valid.
+    pub fn validate(&self, canvas: &Canvas) -> bool {
+        (0..self.placements.len()).all(|grid| {
+            let board = self.board(canvas, grid);
+            SudokuBoard::populated(&board) && SudokuBoard::validate_board(&board)
+        })
+    }
+
+    /// Up to `limit` solutions of the whole canvas.
+    pub fn find_solutions(&self, canvas: &Canvas, limit: usize) -> Vec<Canvas> {
+        let mut state = MultiSearch::new(self);
+        let mut canvas = canvas.clone();
+        for (row, col) in state.cells.clone() {
+            let value = canvas[row][col];
+            if value != 0 {
+                let bit = 1 << value;
+                if state.used(row, col) & bit != 0 {
+                    return Vec::new();
+                }
+                state.toggle(row, col, bit);
+            }
+        }
+        let mut solutions = Vec::new();
+        state.search(&mut canvas, limit, &mut solutions);
+        solutions
+    }
+
+    pub fn count_solutions(&self, canvas: &Canvas, limit: usize) -> usize {
+        self.find_solutions(canvas, limit).len()
+    }
+
+    pub fn solve(&self, canvas: &Canvas) -> Option<Canvas> {
+        self.find_solutions(canvas, 1).pop()
+    }
+
+    /// Reads a puzzle written as an optional `size` line (9 by default),
+    /// one `grid r1c13` line per grid giving its top-left cell, then the
+    /// canvas row by row: a symbol for a given, `.` for an empty cell and a
+    /// space outside every grid. Rows may stop short after their last grid
+    /// cell, and a row outside every grid is a blank line. Blank lines
+    /// beyond the canvas height are dropped from either end.
+    pub fn parse(text: &str) -> Result<(MultiGrid, Canvas), MultiGridError> {
+        let mut size = 9;
+        let mut placements = Vec::new();
+        let mut rows = Vec::new();
+        let mut blank_lines = 0;
+        for line in text.lines() {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            if !rows.is_empty() {
+                rows.push(line);
+                continue;
+            }
+            match words[..] {
+                [] => blank_lines += 1,
+                ["size", side] => {
+                    size = side.parse().map_err(|_| MultiGridError::BadHeader {
+                        line: line.to_string(),
+                    })?;
+                }
+                ["grid", cell] => {
+                    placements.push(parse_cell_name(cell).ok_or(MultiGridError::BadHeader {
+                        line: line.to_string(),
+                    })?);
+                }
+                _ => {
+                    rows.extend(std::iter::repeat_n("", blank_lines));
+                    rows.push(line);
+                }
+            }
+        }
+        if size > MAX_SIZE {
+            return Err(MultiGridError::TooLarge { size });
+        }
+        let box_shape = BoxShape::for_size(size).ok_or(MultiGridError::BadHeader {
+            line: format!("size {}", size),
+        })?;
+        let multi_grid = MultiGrid::new(box_shape, placements)?;
+        while rows.len() > multi_grid.height() && rows.last().is_some_and(|r| r.trim().is_empty()) {
+            rows.pop();
+        }
+        let leading = rows
+            .iter()
+            .take_while(|row| row.trim().is_empty())
+            .count()
+            .min(rows.len().saturating_sub(multi_grid.height()));
+        rows.drain(..leading);
+        if rows.len() != multi_grid.height() {
+            return Err(MultiGridError::WrongRowCount {
+                expected: multi_grid.height(),
+                found: rows.len(),
+            });
+        }
+        let symbols = SymbolSet::for_size(size);
+        let mut canvas = multi_grid.empty();
+        for (row, line) in rows.iter().enumerate() {
+            let mut symbols_in_row: Vec<char> = line.chars().collect();
+            symbols_in_row.resize(symbols_in_row.len().max(multi_grid.width()), ' ');
+            for (col, symbol) in symbols_in_row.into_iter().enumerate() {
+                let inside = col < multi_grid.width() && multi_grid.contains(row, col);
+                match symbol {
+                    ' ' if !inside => {}
+                    '.' if inside => {}
+                    ' ' | '.' => return Err(MultiGridError::WrongCell { row, col }),
+                    _ => {
+                        let value = symbols.value(symbol).ok_or(MultiGridError::UnknownSymbol {
+                            row,
+                            col,
+                            symbol,
+                        })?;
+                        if !inside {
+                            return Err(MultiGridError::WrongCell { row, col });
+                        }
+                        canvas[row][col] = value;
+                    }
+                }
+            }
+        }
+        Ok((multi_grid, canvas))
+    }
+
+    /// Writes the puzzle in the form `parse` reads, leaving out the `size`
+    /// line for classic grids.
+    pub fn to_text(&self, canvas: &Canvas) -> String {
+        let symbols = SymbolSet::for_size(self.size());
+        let mut text = String::new();
+        if self.size() != 9 {
+            text.push_str(&format!("size {}\n", self.size()));
+        }
+        for (row, col) in &self.placements {
+            text.push_str(&format!("grid r{}c{}\n", row + 1, col + 1));
+        }
+        for (row, values) in canvas.iter().enumerate() {
+            let line: String = values
+                .iter()
+                .enumerate()
+                .map(|(col, value)| match (self.contains(row, col), value) {
+                    (false, _) => ' ',
+                    (true, 0) => '.',
+                    (true, value) => symbols.symbol(*value),
+                })
+                .collect();
+            text.push_str(line.trim_end());
+            text.push('\n');
+        }
+        text
+    }
+}
+
+/// The digits placed in each row, column and box of every grid during
+/// `MultiGrid::find_solutions`.
+struct MultiSearch {
+    size: usize,
+    /// Every canvas cell inside a grid.
+    cells: Vec<(usize, usize)>,
+    /// Indices into `masks` of the units each canvas cell is in.
+    cell_units: Vec<Vec<Vec<usize>>>,
+    masks: Vec<u32>,
+}
+
+impl MultiSearch {
+    fn new(multi_grid: &MultiGrid) -> MultiSearch {
+        let size = multi_grid.size();
+        let shape = multi_grid.box_shape;
+        let mut cells = Vec::new();
+        let mut cell_units = vec![vec![Vec::new(); multi_grid.width()]; multi_grid.height()];
+        for (row, row_units) in cell_units.iter_mut().enumerate() {
+            for (col, units) in row_units.iter_mut().enumerate() {
+                for grid in multi_grid.grids_at(row, col) {
+                    let (top, left) = multi_grid.placements[grid];
+                    let (i, j) = (row - top, col - left);
+                    let b = (i / shape.rows) * (size / shape.cols) + j / shape.cols;
+                    let base = grid * 3 * size;
+                    units.extend([base + i, base + size + j, base + 2 * size + b]);
+                }
+                if !units.is_empty() {
+                    cells.push((row, col));
+                }
+            }
+        }
+        MultiSearch {
+            size,
+            cells,
+            cell_units,
+            masks: vec![0; multi_grid.placements.len() * 3 * size],
+        }
+    }
+
+    fn used(&self, row: usize, col: usize) -> u32 {
+        self.cell_units[row][col]
+            .iter()
+            .fold(0, |used, unit| used | self.masks[*unit])
+    }
+
+    fn toggle(&mut self, row: usize, col: usize, bit: u32) {
+        for unit in &self.cell_units[row][col] {
+            self.masks[*unit] ^= bit;
+        }
+    }
+
+    /// Fills the empty cell with the fewest options first.
+    fn search(&mut self, canvas: &mut Canvas, limit: usize, solutions: &mut Vec<Canvas>) {
+        let all_values: u32 = ((1u32 << (self.size + 1)) - 1) & !1;
+        let mut best: Option<((usize, usize), u32)> = None;
+        for (row, col) in &self.cells {
+            if canvas[*row][*col] != 0 {
+                continue;
+            }
+            let options = !self.used(*row, *col) & all_values;
+            if best.is_none_or(|(_, fewest)| options.count_ones() < fewest.count_ones()) {
+                best = Some(((*row, *col), options));
+                if options.count_ones() <= 1 {
+                    break;
+                }
+            }
+        }
+        let ((row, col), options) = match best {
+            Some(best) => best,
+            None => {
+                solutions.push(canvas.clone());
+                return;
+            }
+        };
+        for value in 1..=self.size as i32 {
+            let bit = 1 << value;
+            if options & bit == 0 {
+                continue;
+            }
+            canvas[row][col] = value;
+            self.toggle(row, col, bit);
+            self.search(canvas, limit, solutions);
+            self.toggle(row, col, bit);
+            canvas[row][col] = 0;
+            if solutions.len() >= limit {
+                return;
+            }
+        }
+    }
+}
+
+#[test]
+fn solves_samurai() {
+    let text = "\
+grid r1c1
+grid r1c13
+grid r7c7
+grid r13c1
+grid r13c13
+...4...89   .51.4.7..
+.5.7..1..   6...8...4
+7.9.23...   .....7...
+.3......5   4.3.5....
+.759.....   .2.9..3..
+6....8.17   .9641..7.
+3..2.59......678.14.3
+5..8.7...5.......56..
+..834..............2.
+      .........
+      .5.9.2...
+      .8931....
+...78....63....1...6.
+..9.2...4...........8
+....56....45.7.4..23.
+26.83....   ..5...7..
+..36...28   43791...2
+...2.7...   8.9.3..1.
+.1...89..   2..5.....
+.5..6..3.   .1.79....
+...5....1   ......8..
+";
+    let (samurai, canvas) = MultiGrid::parse(text).unwrap();
+    assert_eq!(samurai, MultiGrid::samurai());
+    assert_eq!((samurai.height(), samurai.width()), (21, 21));
+    assert_eq!(samurai.to_text(&canvas), text);
+    assert_eq!(samurai.grids_at(6, 7), vec![0, 2]);
+    // The top-left and centre grids each leave more than the shared cell
+    // can take.
+    assert_eq!(
+        samurai.board(&canvas, 0).possible_solutions[6][7],
+        vec![4, 6, 7]
+    );
+    assert_eq!(
+        samurai.board(&canvas, 2).possible_solutions[0][1],
+        vec![1, 2, 3, 4]
+    );
+    assert_eq!(samurai.candidates(&canvas)[6][7], vec![4]);
+
+    assert_eq!(samurai.count_solutions(&canvas, 2), 1);
+    let solved = samurai.solve(&canvas).unwrap();
+    assert!(samurai.validate(&solved));
+    assert_eq!(
+        SudokuBoard::to_puzzle_string(&samurai.board(&solved, 2).puzzle),
+        "948123567631579248572468139126754893357982416489316725215637984764891352893245671"
+    );
+    assert!(!samurai.validate(&canvas));
+}
+
+#[test]
+fn reads_multi_grid_layouts() {
+    let twodoku = MultiGrid::twodoku();
+    assert_eq!((twodoku.height(), twodoku.width()), (15, 15));
+    assert!(twodoku.contains(7, 7));
+    assert!(!twodoku.contains(0, 12));
+    assert_eq!(MultiGrid::butterfly().grids_at(5, 5), vec![0, 1, 2, 3]);
+    let solved = twodoku.solve(&twodoku.empty()).unwrap();
+    assert!(twodoku.validate(&solved));
+
+    assert_eq!(
+        MultiGrid::new(BoxShape::CLASSIC, vec![(0, 0), (2, 6)]),
+        Err(MultiGridError::Misaligned { grid: 1 })
+    );
+    assert_eq!(
+        MultiGrid::new(BoxShape::new(2, 2), vec![(0, 0), (2, 2)]),
+        Ok(MultiGrid {
+            box_shape: BoxShape::new(2, 2),
+            placements: vec![(0, 0), (2, 2)]
+        })
+    );
+    assert_eq!(
+        MultiGrid::parse("grid r1c1\ngrid r7c7\n123"),
+        Err(MultiGridError::WrongRowCount {
+            expected: 15,
+            found: 1
+        })
+    );
+    let layout = "size 4\ngrid r1c1\ngrid r3c3\n1...\n....\n.... .\n......\n  ....\n  ....\n";
+    assert_eq!(
+        MultiGrid::parse(layout),
+        Err(MultiGridError::WrongCell { row: 2, col: 4 })
+    );
+    let (small, canvas) = MultiGrid::parse(&layout.replace(".... .", "......")).unwrap();
+    assert_eq!(small.size(), 4);
+    assert_eq!(small.count_solutions(&canvas, 2), 2);
+}
+
+#[test]
+fn keeps_blank_canvas_rows() {
+    for placements in [vec![(0, 0), (6, 0)], vec![(2, 1), (8, 0)]] {
+        let gapped = MultiGrid::new(BoxShape::new(2, 2), placements).unwrap();
+        let solved = gapped.solve(&gapped.empty()).unwrap();
+        let text = gapped.to_text(&solved);
+        assert_eq!(
+            MultiGrid::parse(&text),
+            Ok((gapped.clone(), solved.clone()))
+        );
+        let spaced = text.replacen("\n", "\n\n", 3) + "\n\n";
+        assert_eq!(MultiGrid::parse(&spaced), Ok((gapped, solved)));
+    }
+
+    assert_eq!(
+        MultiGrid::new(BoxShape::new(6, 6), vec![(0, 0)]),
+        Err(MultiGridError::TooLarge { size: 36 })
+    );
+    assert_eq!(
+        MultiGrid::parse("size 36\ngrid r1c1\n"),
+        Err(MultiGridError::TooLarge { size: 36 })
+    );
+}
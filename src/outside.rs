@@ -0,0 +1,306 @@
+use std::fmt;
+
+/// A side of the grid, where an outside clue sits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Side {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+impl Side {
+    /// The step from a cell on this side one cell into the grid.
+    fn inward(&self) -> (i32, i32) {
+        match self {
+            Side::Top => (1, 0),
+            Side::Bottom => (-1, 0),
+            Side::Left => (0, 1),
+            Side::Right => (0, -1),
+        }
+    }
+
+    /// The step one cell towards this side.
+    fn towards(&self) -> (i32, i32) {
+        let (dr, dc) = self.inward();
+        (-dr, -dc)
+    }
+
+    fn is_horizontal(&self) -> bool {
+        matches!(self, Side::Top | Side::Bottom)
+    }
+}
+
+/// What an outside clue says about the cells it looks along.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClueKind {
+    /// The sum of the digits between the 1 and the highest digit of the row
+    /// or column.
+    Sandwich,
+    /// How many digits can be seen from the clue, taller digits hiding
+    /// shorter ones behind them.
+    Skyscraper,
+    /// The sum along a diagonal running into the grid and towards the given
+    /// side, e.g. down and to the right for a `Top` clue heading `Right`.
+    /// Digits may repeat unless they share a box.
+    LittleKiller(Side),
+}
+
+impl ClueKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ClueKind::Sandwich => "sandwich clue",
+            ClueKind::Skyscraper => "skyscraper clue",
+            ClueKind::LittleKiller(_) => "little killer clue",
+        }
+    }
+}
+
+impl fmt::Display for ClueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// A clue outside the grid, next to row or column `index` on `side`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OutsideClue {
+    pub kind: ClueKind,
+    pub side: Side,
+    pub index: usize,
+    pub value: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClueError {
+    /// There is no row or column `index` on the board.
+    IndexOutOfRange { index: usize },
+    /// A little killer must head along its side, e.g. left or right from the
+    /// top.
+    NotDiagonal,
+}
+
+impl fmt::Display for ClueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClueError::IndexOutOfRange { index } => {
+                write!(f, "there is no row or column {}", index + 1)
+            }
+            ClueError::NotDiagonal => write!(f, "little killer does not run diagonally"),
+        }
+    }
+}
+
+impl std::error::Error for ClueError {}
+
+impl OutsideClue {
+    pub fn new(kind: ClueKind, side: Side, index: usize, value: i32) -> OutsideClue {
+        OutsideClue {
+            kind,
+            side,
+            index,
+            value,
+        }
+    }
+
+    pub fn check(&self, size: usize) -> Result<(), ClueError> {
+        if self.index >= size {
+            return Err(ClueError::IndexOutOfRange { index: self.index });
+        }
+        if let ClueKind::LittleKiller(heading) = self.kind {
+            if heading.is_horizontal() == self.side.is_horizontal() {
+                return Err(ClueError::NotDiagonal);
+            }
+        }
+        Ok(())
+    }
+
+    /// The cells the clue looks along, nearest first.
+    pub fn cells(&self, size: usize) -> Vec<(usize, usize)> {
+        let last = size - 1;
+        let start = match self.side {
+            Side::Top => (0, self.index),
+            Side::Bottom => (last, self.index),
+            Side::Left => (self.index, 0),
+            Side::Right => (self.index, last),
+        };
+        let (mut dr, mut dc) = self.side.inward();
+        if let ClueKind::LittleKiller(heading) = self.kind {
+            dr += heading.towards().0;
+            dc += heading.towards().1;
+        }
+        let mut cells = Vec::new();
+        let (mut row, mut col) = (start.0 as i32, start.1 as i32);
+        while row >= 0 && col >= 0 && row < size as i32 && col < size as i32 {
+            cells.push((row as usize, col as usize));
+            row += dr;
+            col += dc;
+        }
+        cells
+    }
+
+    /// Where the clue is drawn, one step outside the grid from its first
+    /// cell; coordinates run from -1 to `size`.
+    pub fn position(&self, size: usize) -> (i32, i32) {
+        let (row, col) = self.cells(size)[0];
+        let (mut dr, mut dc) = self.side.inward();
+        if let ClueKind::LittleKiller(heading) = self.kind {
+            dr += heading.towards().0;
+            dc += heading.towards().1;
+        }
+        (row as i32 - dr, col as i32 - dc)
+    }
+
+    /// The clue as drawn: its value, with an arrow for a little killer.
+    pub fn label(&self, size: usize) -> String {
+        match self.kind {
+            ClueKind::LittleKiller(_) => {
+                let (row, col) = self.position(size);
+                let (first_row, first_col) = self.cells(size)[0];
+                let arrow = match (first_row as i32 > row, first_col as i32 > col) {
+                    (true, true) => '↘',
+                    (true, false) => '↙',
+                    (false, true) => '↗',
+                    (false, false) => '↖',
+                };
+                format!("{}{}", self.value, arrow)
+            }
+            _ => self.value.to_string(),
+        }
+    }
+
+    /// Whether the digits along the clue's cells, nearest first with 0 for
+    /// an empty cell, can still satisfy it on a board of side `size`. Exact
+    /// when every cell is filled.
+    pub fn allows(&self, values: &[i32], size: usize) -> bool {
+        let size = size as i32;
+        match self.kind {
+            ClueKind::Sandwich => {
+                let low = values.iter().position(|value| *value == 1);
+                let high = values.iter().position(|value| *value == size);
+                let (first, last) = match (low, high) {
+                    (Some(low), Some(high)) => (low.min(high), low.max(high)),
+                    _ => return true,
+                };
+                let between = &values[first + 1..last];
+                let sum: i32 = between.iter().sum();
+                let empty = between.iter().filter(|value| **value == 0).count() as i32;
+                sum + 2 * empty <= self.value && sum + (size - 1) * empty >= self.value
+            }
+            ClueKind::Skyscraper => {
+                let mut seen = 0;
+                let mut tallest = 0;
+                let mut filled = 0;
+                for value in values.iter().take_while(|value| **value != 0) {
+                    if *value > tallest {
+                        seen += 1;
+                        tallest = *value;
+                    }
+                    filled += 1;
+                }
+                let remaining = (values.len() - filled) as i32;
+                // At most the cells up to a digit and the taller digits after
+                // it can be seen.
+                let reachable = values
+                    .iter()
+                    .enumerate()
+                    .all(|(i, value)| *value == 0 || i as i32 + 1 + size - value >= self.value);
+                // The highest digit is always seen, even if it is still to
+                // be placed.
+                let still_seen = (tallest < size) as i32;
+                reachable
+                    && seen + still_seen <= self.value
+                    && seen + remaining.min(size - tallest) >= self.value
+            }
+            ClueKind::LittleKiller(_) => {
+                let sum: i32 = values.iter().sum();
+                let empty = values.iter().filter(|value| **value == 0).count() as i32;
+                sum + empty <= self.value && sum + size * empty >= self.value
+            }
+        }
+    }
+}
+
+#[test]
+fn reads_outside_clues() {
+    let sandwich = OutsideClue::new(ClueKind::Sandwich, Side::Left, 0, 14);
+    assert_eq!(sandwich.cells(9)[8], (0, 8));
+    assert!(sandwich.allows(&[2, 1, 3, 4, 7, 9, 5, 6, 8], 9));
+    assert!(!sandwich.allows(&[1, 2, 3, 4, 5, 6, 7, 8, 9], 9));
+    assert!(sandwich.allows(&[9, 6, 8, 1, 0, 0, 0, 0, 0], 9));
+    assert!(!sandwich.allows(&[9, 6, 7, 1, 0, 0, 0, 0, 0], 9));
+    assert!(sandwich.allows(&[9, 0, 0, 1, 0, 0, 0, 0, 0], 9));
+    assert!(!sandwich.allows(&[9, 1, 0, 0, 0, 0, 0, 0, 0], 9));
+
+    let skyscraper = OutsideClue::new(ClueKind::Skyscraper, Side::Right, 2, 3);
+    assert_eq!(skyscraper.cells(9)[0], (2, 8));
+    assert!(skyscraper.allows(&[4, 2, 7, 9, 1, 3, 5, 6, 8], 9));
+    assert!(!skyscraper.allows(&[4, 2, 7, 8, 0, 0, 0, 0, 0], 9));
+    assert!(!skyscraper.allows(&[9, 0, 0, 0, 0, 0, 0, 0, 0], 9));
+    assert!(!skyscraper.allows(&[0, 9, 0, 0, 0, 0, 0, 0, 0], 9));
+
+    let little_killer = OutsideClue::new(ClueKind::LittleKiller(Side::Right), Side::Top, 6, 15);
+    assert_eq!(little_killer.cells(9), vec![(0, 6), (1, 7), (2, 8)]);
+    assert_eq!(little_killer.position(9), (-1, 5));
+    assert_eq!(little_killer.label(9), "15↘");
+    assert!(little_killer.allows(&[9, 0, 0], 9));
+    assert!(!little_killer.allows(&[9, 6, 0], 9));
+    assert_eq!(
+        OutsideClue::new(ClueKind::LittleKiller(Side::Left), Side::Right, 0, 5).check(9),
+        Err(ClueError::NotDiagonal)
+    );
+}
+
+#[test]
+fn solves_outside_clues() {
+    use crate::logic::{self, Technique, Unit};
+    use crate::sudoku::SudokuBoard;
+
+    let mut board = SudokuBoard::from_puzzle_string(
+        ".....1.2.3.7..2.............64.9....21.....6.......................4...98........",
+    )
+    .unwrap();
+    assert_eq!(board.possible_solutions[0][4], vec![3, 5, 6, 7, 8]);
+    let little_killer = ClueKind::LittleKiller(Side::Right);
+    let clues = vec![
+        OutsideClue::new(ClueKind::Skyscraper, Side::Top, 4, 4),
+        OutsideClue::new(ClueKind::Sandwich, Side::Left, 5, 6),
+        OutsideClue::new(ClueKind::Sandwich, Side::Left, 6, 18),
+        OutsideClue::new(ClueKind::Sandwich, Side::Left, 7, 3),
+        OutsideClue::new(ClueKind::Skyscraper, Side::Top, 7, 2),
+        OutsideClue::new(ClueKind::Skyscraper, Side::Top, 8, 2),
+        OutsideClue::new(little_killer, Side::Top, 5, 15),
+        OutsideClue::new(little_killer, Side::Top, 6, 20),
+    ];
+    SudokuBoard::add_outside_clues(&mut board, clues).unwrap();
+    // Four skyscrapers must be seen from above column 5, which a 7 or 8 in
+    // r1c5 would hide.
+    assert_eq!(board.possible_solutions[0][4], vec![3, 5, 6]);
+
+    let path = logic::solve_path(&board);
+    assert!(path.solved());
+    let clue_step = |unit: Unit| {
+        path.steps
+            .iter()
+            .find(|step| step.technique == Technique::ClueElimination && step.units == [unit])
+            .unwrap()
+    };
+    assert_eq!(
+        clue_step(Unit::Clue(0, ClueKind::Skyscraper)).eliminations,
+        vec![(0, 4, 6), (2, 4, 3), (2, 4, 5)]
+    );
+    assert_eq!(
+        clue_step(Unit::Clue(6, little_killer)).eliminations,
+        vec![(3, 8, 7), (3, 8, 8)]
+    );
+    assert!(!clue_step(Unit::Clue(1, ClueKind::Sandwich))
+        .eliminations
+        .is_empty());
+    assert_eq!(
+        SudokuBoard::add_outside_clues(
+            &mut board,
+            vec![OutsideClue::new(ClueKind::Sandwich, Side::Top, 9, 0)]
+        ),
+        Err(ClueError::IndexOutOfRange { index: 9 })
+    );
+}
@@ -0,0 +1,368 @@
+use std::fmt;
+
+/// How the digits of two cells relate. Kropki puzzles mark `Consecutive`
+/// pairs with a white dot and `Ratio` pairs with a black dot; 1 and 2 fit
+/// either. XV puzzles mark pairs adding up to 5 with a V and to 10 with an
+/// X.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PairRelation {
+    /// The digits differ by one.
+    Consecutive,
+    /// One digit is twice the other.
+    Ratio,
+    NotConsecutive,
+    /// Neither consecutive nor in ratio: an undotted pair under the Kropki
+    /// negative constraint.
+    Neither,
+    /// The digits add up to 5.
+    V,
+    /// The digits add up to 10.
+    X,
+    /// The digits add up to neither 5 nor 10: an unmarked pair under the XV
+    /// negative constraint.
+    NeitherXV,
+}
+
+impl PairRelation {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PairRelation::Consecutive => "white dot",
+            PairRelation::Ratio => "black dot",
+            PairRelation::NotConsecutive => "non-consecutive pair",
+            PairRelation::Neither => "undotted pair",
+            PairRelation::V => "V",
+            PairRelation::X => "X",
+            PairRelation::NeitherXV => "unmarked XV pair",
+        }
+    }
+
+    /// What the relation requires, e.g. "consecutive".
+    pub fn description(&self) -> &'static str {
+        match self {
+            PairRelation::Consecutive => "consecutive",
+            PairRelation::Ratio => "in a 1:2 ratio",
+            PairRelation::NotConsecutive => "not consecutive",
+            PairRelation::Neither => "neither consecutive nor in a 1:2 ratio",
+            PairRelation::V => "a pair adding up to 5",
+            PairRelation::X => "a pair adding up to 10",
+            PairRelation::NeitherXV => "a pair adding up to neither 5 nor 10",
+        }
+    }
+
+    /// Whether digits `a` and `b` satisfy the relation, in either order.
+    pub fn allows(&self, a: i32, b: i32) -> bool {
+        let consecutive = (a - b).abs() == 1;
+        let ratio = a == 2 * b || b == 2 * a;
+        match self {
+            PairRelation::Consecutive => consecutive,
+            PairRelation::Ratio => ratio,
+            PairRelation::NotConsecutive => !consecutive,
+            PairRelation::Neither => !consecutive && !ratio,
+            PairRelation::V => a + b == 5,
+            PairRelation::X => a + b == 10,
+            PairRelation::NeitherXV => a + b != 5 && a + b != 10,
+        }
+    }
+}
+
+impl fmt::Display for PairRelation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// A relation between the digits of two cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PairConstraint {
+    pub a: (usize, usize),
+    pub b: (usize, usize),
+    pub relation: PairRelation,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PairError {
+    CellOutOfRange {
+        row: usize,
+        col: usize,
+    },
+    /// Both ends of a pair are the same cell.
+    SameCell {
+        row: usize,
+        col: usize,
+    },
+}
+
+impl fmt::Display for PairError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PairError::CellOutOfRange { row, col } => {
+                write!(f, "r{}c{} is outside the board", row + 1, col + 1)
+            }
+            PairError::SameCell { row, col } => {
+                write!(f, "r{}c{} is paired with itself", row + 1, col + 1)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PairError {}
+
+impl PairConstraint {
+    pub fn new(a: (usize, usize), b: (usize, usize), relation: PairRelation) -> PairConstraint {
+        PairConstraint { a, b, relation }
+    }
+
+    /// The other end of the pair, if `cell` is one end.
+    pub fn partner(&self, cell: (usize, usize)) -> Option<(usize, usize)> {
+        if cell == self.a {
+            Some(self.b)
+        } else if cell == self.b {
+            Some(self.a)
+        } else {
+            None
+        }
+    }
+
+    /// Checks that both cells are on a board of side `size` and differ.
+    pub fn check(&self, size: usize) -> Result<(), PairError> {
+        for (row, col) in [self.a, self.b] {
+            if row >= size || col >= size {
+                return Err(PairError::CellOutOfRange { row, col });
+            }
+        }
+        if self.a == self.b {
+            return Err(PairError::SameCell {
+                row: self.a.0,
+                col: self.a.1,
+            });
+        }
+        Ok(())
+    }
+
+    /// Non-consecutive sudoku: every pair of orthogonal neighbours.
+    pub fn non_consecutive(size: usize) -> Vec<PairConstraint> {
+        orthogonal_pairs(size)
+            .into_iter()
+            .map(|(a, b)| PairConstraint::new(a, b, PairRelation::NotConsecutive))
+            .collect()
+    }
+
+    /// The Kropki negative constraint: every pair of orthogonal neighbours
+    /// without one of `dots` is neither consecutive nor in ratio.
+    pub fn kropki_negative(size: usize, dots: &[PairConstraint]) -> Vec<PairConstraint> {
+        unmarked_pairs(size, dots, PairRelation::Neither)
+    }
+
+    /// The XV negative constraint: every pair of orthogonal neighbours
+    /// without one of `marks` adds up to neither 5 nor 10.
+    pub fn xv_negative(size: usize, marks: &[PairConstraint]) -> Vec<PairConstraint> {
+        unmarked_pairs(size, marks, PairRelation::NeitherXV)
+    }
+}
+
+/// `relation` on each pair of orthogonal neighbours not already in `marks`.
+fn unmarked_pairs(
+    size: usize,
+    marks: &[PairConstraint],
+    relation: PairRelation,
+) -> Vec<PairConstraint> {
+    orthogonal_pairs(size)
+        .into_iter()
+        .filter(|(a, b)| !marks.iter().any(|mark| mark.partner(*a) == Some(*b)))
+        .map(|(a, b)| PairConstraint::new(a, b, relation))
+        .collect()
+}
+
+/// Each pair of horizontally or vertically adjacent cells, once.
+fn orthogonal_pairs(size: usize) -> Vec<((usize, usize), (usize, usize))> {
+    let mut pairs = Vec::new();
+    for i in 0..size {
+        for j in 0..size {
+            if j + 1 < size {
+                pairs.push(((i, j), (i, j + 1)));
+            }
+            if i + 1 < size {
+                pairs.push(((i, j), (i + 1, j)));
+            }
+        }
+    }
+    pairs
+}
+
+#[test]
+fn relates_pairs_of_digits() {
+    assert!(PairRelation::Consecutive.allows(4, 5));
+    assert!(PairRelation::Consecutive.allows(5, 4));
+    assert!(!PairRelation::Consecutive.allows(4, 6));
+    assert!(PairRelation::Ratio.allows(3, 6));
+    assert!(PairRelation::Ratio.allows(2, 1));
+    assert!(PairRelation::Consecutive.allows(2, 1));
+    assert!(!PairRelation::Neither.allows(4, 8));
+    assert!(PairRelation::Neither.allows(3, 9));
+    assert!(PairRelation::X.allows(3, 7));
+    assert!(!PairRelation::X.allows(3, 2));
+    assert!(PairRelation::V.allows(1, 4));
+    assert!(!PairRelation::NeitherXV.allows(4, 6));
+    assert!(PairRelation::NeitherXV.allows(4, 7));
+
+    assert_eq!(PairConstraint::non_consecutive(9).len(), 144);
+    let dot = PairConstraint::new((0, 1), (0, 0), PairRelation::Ratio);
+    assert_eq!(PairConstraint::kropki_negative(9, &[dot]).len(), 143);
+    let x = PairConstraint::new((4, 4), (5, 4), PairRelation::X);
+    let unmarked = PairConstraint::xv_negative(9, &[x]);
+    assert_eq!(unmarked.len(), 143);
+    assert!(unmarked
+        .iter()
+        .all(|pair| pair.relation == PairRelation::NeitherXV));
+    assert_eq!(dot.partner((0, 0)), Some((0, 1)));
+    assert_eq!(
+        PairConstraint::new((0, 0), (0, 0), PairRelation::Ratio).check(9),
+        Err(PairError::SameCell { row: 0, col: 0 })
+    );
+}
+
+#[test]
+fn solves_non_consecutive_and_kropki() {
+    use crate::explain::explain_step;
+    use crate::logic::{self, Step, Technique, Unit};
+    use crate::sudoku::SudokuBoard;
+
+    let pair_elimination = |path: &logic::SolvePath| {
+        path.steps
+            .iter()
+            .find(|step| step.technique == Technique::PairElimination)
+            .cloned()
+            .unwrap()
+    };
+
+    let mut board = SudokuBoard::from_puzzle_string(
+        "..4....6.....68.9....4.5......1....9.......5.......4..6.....9............4.......",
+    )
+    .unwrap();
+    SudokuBoard::add_pair_constraints(&mut board, PairConstraint::non_consecutive(9)).unwrap();
+    // r2c4 sits next to the 6 in r2c5, so 7 is out.
+    assert_eq!(board.possible_solutions[1][3], vec![2]);
+    let path = logic::solve_path(&board);
+    assert!(path.solved());
+    let step = pair_elimination(&path);
+    assert!(matches!(
+        step.units[..],
+        [Unit::Pair(_, PairRelation::NotConsecutive)]
+    ));
+    assert_eq!(step.eliminations, vec![(0, 6, 2)]);
+
+    // White and black dots wherever this grid has them, with the negative
+    // constraint on every other neighbouring pair.
+    let solution = SudokuBoard::from_puzzle_string(
+        "628415793497623518135978624842796135516832947379154862283569471951347286764281359",
+    )
+    .unwrap()
+    .puzzle;
+    let mut dots = Vec::new();
+    for pair in PairConstraint::kropki_negative(9, &[]) {
+        let (a, b) = (solution[pair.a.0][pair.a.1], solution[pair.b.0][pair.b.1]);
+        for relation in [PairRelation::Consecutive, PairRelation::Ratio] {
+            if relation.allows(a, b) {
+                dots.push(PairConstraint::new(pair.a, pair.b, relation));
+                break;
+            }
+        }
+    }
+    let mut kropki = SudokuBoard::new();
+    kropki.puzzle[0][1] = 2;
+    kropki.puzzle[7][3] = 3;
+    let negative = PairConstraint::kropki_negative(9, &dots);
+    dots.extend(negative);
+    SudokuBoard::add_pair_constraints(&mut kropki, dots).unwrap();
+    // No dot joins r1c3 to the 2 in r1c2, so 1, 3 and 4 are out.
+    assert_eq!(kropki.possible_solutions[0][2], vec![5, 6, 7, 8, 9]);
+    let path = logic::solve_path(&kropki);
+    assert_eq!(path.result.puzzle, solution);
+    let step = pair_elimination(&path);
+    assert_eq!(step.cells, vec![(0, 2), (0, 3)]);
+    assert_eq!(
+        step.eliminations,
+        vec![(0, 2, 5), (0, 2, 6), (0, 2, 7), (0, 2, 9)]
+    );
+
+    let mut dot = SudokuBoard::new();
+    let ratio = PairConstraint::new((0, 0), (0, 1), PairRelation::Ratio);
+    SudokuBoard::add_pair_constraints(&mut dot, vec![ratio]).unwrap();
+    let step = logic::find_step(&dot, Technique::PairElimination).unwrap();
+    assert_eq!(
+        explain_step(&step),
+        "Black dot: r1c1 and r1c2 must be in a 1:2 ratio, so 5, 7 and 9 can be removed from r1c1 (pair elimination)"
+    );
+    // Placing a digit leaves its partner's candidates to a pair elimination.
+    let placement = Step {
+        technique: Technique::NakedSingle,
+        units: Vec::new(),
+        cells: vec![(0, 0)],
+        digits: vec![3],
+        placement: Some((0, 0, 3)),
+        eliminations: Vec::new(),
+    };
+    logic::apply_step(&mut dot, &placement);
+    assert!(dot.possible_solutions[0][1].contains(&5));
+    let step = logic::find_step(&dot, Technique::PairElimination).unwrap();
+    logic::apply_step(&mut dot, &step);
+    assert_eq!(dot.possible_solutions[0][1], vec![6]);
+}
+
+#[test]
+fn solves_xv() {
+    use crate::logic::{self, Technique, Unit};
+    use crate::sudoku::SudokuBoard;
+
+    // X and V marks wherever this grid has them, with the negative
+    // constraint on every other neighbouring pair.
+    let solution = SudokuBoard::from_puzzle_string(
+        "874361592931725468526498137193586274657234981482179356218957643745613829369842715",
+    )
+    .unwrap()
+    .puzzle;
+    let marks: Vec<PairConstraint> = PairConstraint::xv_negative(9, &[])
+        .into_iter()
+        .filter_map(
+            |pair| match solution[pair.a.0][pair.a.1] + solution[pair.b.0][pair.b.1] {
+                5 => Some(PairConstraint::new(pair.a, pair.b, PairRelation::V)),
+                10 => Some(PairConstraint::new(pair.a, pair.b, PairRelation::X)),
+                _ => None,
+            },
+        )
+        .collect();
+    let mut pairs = PairConstraint::xv_negative(9, &marks);
+    pairs.extend(marks);
+    let mut board = SudokuBoard::new();
+    board.puzzle[3][4] = 8;
+    board.puzzle[5][3] = 1;
+    SudokuBoard::add_pair_constraints(&mut board, pairs).unwrap();
+    // The X below the 1 in r6c4 leaves only 9, and with no mark beside it
+    // r5c4 can be neither 4 nor 9.
+    assert_eq!(board.possible_solutions[6][3], vec![9]);
+    assert_eq!(board.possible_solutions[4][3], vec![2, 3, 5, 6, 7]);
+
+    let path = logic::solve_path(&board);
+    assert_eq!(path.result.puzzle, solution);
+    let pair_step = |relation: PairRelation| {
+        path.steps
+            .iter()
+            .find(|step| {
+                step.technique == Technique::PairElimination
+                    && matches!(step.units[..], [Unit::Pair(_, r)] if r == relation)
+            })
+            .unwrap()
+    };
+    let v = pair_step(PairRelation::V);
+    assert_eq!(v.cells, vec![(0, 2), (1, 2)]);
+    assert_eq!(
+        v.eliminations,
+        vec![(0, 2, 5), (0, 2, 6), (0, 2, 7), (0, 2, 8), (0, 2, 9)]
+    );
+    let x = pair_step(PairRelation::X);
+    assert_eq!(x.cells, vec![(0, 1), (1, 1)]);
+    assert_eq!(
+        x.eliminations,
+        vec![(0, 1, 1), (0, 1, 2), (0, 1, 3), (0, 1, 4), (0, 1, 5)]
+    );
+}
@@ -0,0 +1,154 @@
+use std::fmt;
+
+/// Whether a marked cell holds an even or an odd digit. Puzzles usually
+/// shade even cells with a square and odd cells with a circle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Parity {
+    Even,
+    Odd,
+}
+
+impl Parity {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Parity::Even => "even cell",
+            Parity::Odd => "odd cell",
+        }
+    }
+
+    pub fn allows(&self, value: i32) -> bool {
+        match self {
+            Parity::Even => value % 2 == 0,
+            Parity::Odd => value % 2 == 1,
+        }
+    }
+}
+
+impl fmt::Display for Parity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// A cell restricted to even or odd digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ParityCell {
+    pub cell: (usize, usize),
+    pub parity: Parity,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParityError {
+    CellOutOfRange {
+        row: usize,
+        col: usize,
+    },
+    /// The layout is not a square grid.
+    NotSquare,
+    /// A layout character other than `e`, `o` or `.`.
+    UnknownMark {
+        mark: char,
+    },
+}
+
+impl fmt::Display for ParityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParityError::CellOutOfRange { row, col } => {
+                write!(f, "r{}c{} is outside the board", row + 1, col + 1)
+            }
+            ParityError::NotSquare => write!(f, "parity layout is not a square grid"),
+            ParityError::UnknownMark { mark } => write!(f, "'{}' is not a parity mark", mark),
+        }
+    }
+}
+
+impl std::error::Error for ParityError {}
+
+impl ParityCell {
+    pub fn new(cell: (usize, usize), parity: Parity) -> ParityCell {
+        ParityCell { cell, parity }
+    }
+
+    /// Marks from a square layout written one character per cell, ignoring
+    /// whitespace: `e` for even, `o` for odd and `.` for an unmarked cell.
+    pub fn parse(layout: &str) -> Result<Vec<ParityCell>, ParityError> {
+        let marks: Vec<char> = layout.chars().filter(|c| !c.is_whitespace()).collect();
+        let size = (1..=marks.len())
+            .find(|size| size * size == marks.len())
+            .ok_or(ParityError::NotSquare)?;
+        let mut cells = Vec::new();
+        for (index, mark) in marks.into_iter().enumerate() {
+            let parity = match mark {
+                'e' => Parity::Even,
+                'o' => Parity::Odd,
+                '.' => continue,
+                _ => return Err(ParityError::UnknownMark { mark }),
+            };
+            cells.push(ParityCell::new((index / size, index % size), parity));
+        }
+        Ok(cells)
+    }
+
+    /// Checks that the cell is on a board of side `size`.
+    pub fn check(&self, size: usize) -> Result<(), ParityError> {
+        let (row, col) = self.cell;
+        if row >= size || col >= size {
+            return Err(ParityError::CellOutOfRange { row, col });
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn reads_parity_marks() {
+    assert!(Parity::Even.allows(4));
+    assert!(!Parity::Even.allows(7));
+    assert!(Parity::Odd.allows(9));
+
+    let cells = ParityCell::parse("e... .o.. .... ...e").unwrap();
+    assert_eq!(
+        cells,
+        vec![
+            ParityCell::new((0, 0), Parity::Even),
+            ParityCell::new((1, 1), Parity::Odd),
+            ParityCell::new((3, 3), Parity::Even),
+        ]
+    );
+    assert_eq!(
+        cells[2].check(3),
+        Err(ParityError::CellOutOfRange { row: 3, col: 3 })
+    );
+    assert_eq!(
+        ParityCell::parse("e.x."),
+        Err(ParityError::UnknownMark { mark: 'x' })
+    );
+    assert_eq!(ParityCell::parse("e.."), Err(ParityError::NotSquare));
+}
+
+#[test]
+fn solves_even_odd() {
+    use crate::logic::{self, Technique, Unit};
+    use crate::sudoku::SudokuBoard;
+
+    let mut board = SudokuBoard::from_puzzle_string(
+        "..94....7....5........8.....4...2....6.....8....83..1...2....9.....9....3......6.",
+    )
+    .unwrap();
+    assert_eq!(board.possible_solutions[0][7], vec![2, 3, 5]);
+    let layout =
+        "......eeo ......ooo o........ oe....e.. ..o...... ..o.o.... eo.ee.... ......oe. .oeo...e.";
+    SudokuBoard::add_parity_cells(&mut board, ParityCell::parse(layout).unwrap()).unwrap();
+    assert_eq!(board.possible_solutions[0][7], vec![2]);
+
+    let path = logic::solve_path(&board);
+    assert!(path.solved());
+    // Odd-shaded r2c8 is the only place left for the 3 of column 8.
+    let step = path
+        .steps
+        .iter()
+        .find(|step| step.placement == Some((1, 7, 3)))
+        .unwrap();
+    assert_eq!(step.technique, Technique::HiddenSingle);
+    assert_eq!(step.units, vec![Unit::Col(7)]);
+}
@@ -0,0 +1,122 @@
+use std::fmt;
+
+/// A global rule forbidding equal digits in cells a fixed chess move apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PeerRule {
+    AntiKnight,
+    /// Only the diagonal king moves add anything; the others stay within a
+    /// row or column.
+    AntiKing,
+}
+
+const KNIGHT_MOVES: [(i32, i32); 8] = [
+    (-2, -1),
+    (-2, 1),
+    (-1, -2),
+    (-1, 2),
+    (1, -2),
+    (1, 2),
+    (2, -1),
+    (2, 1),
+];
+
+const KING_MOVES: [(i32, i32); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+impl PeerRule {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PeerRule::AntiKnight => "anti-knight",
+            PeerRule::AntiKing => "anti-king",
+        }
+    }
+
+    fn moves(&self) -> &'static [(i32, i32)] {
+        match self {
+            PeerRule::AntiKnight => &KNIGHT_MOVES,
+            PeerRule::AntiKing => &KING_MOVES,
+        }
+    }
+
+    /// The cells one move from (row, col) on a board of side `size`.
+    pub fn cells(&self, row: usize, col: usize, size: usize) -> Vec<(usize, usize)> {
+        self.moves()
+            .iter()
+            .map(|(dr, dc)| (row as i32 + dr, col as i32 + dc))
+            .filter(|(r, c)| *r >= 0 && *c >= 0 && *r < size as i32 && *c < size as i32)
+            .map(|(r, c)| (r as usize, c as usize))
+            .collect()
+    }
+
+    /// Whether `a` and `b` are one move apart.
+    pub fn relates(&self, a: (usize, usize), b: (usize, usize)) -> bool {
+        let step = (b.0 as i32 - a.0 as i32, b.1 as i32 - a.1 as i32);
+        self.moves().contains(&step)
+    }
+}
+
+impl fmt::Display for PeerRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[test]
+fn finds_chess_move_cells() {
+    assert_eq!(PeerRule::AntiKnight.cells(0, 0, 9), vec![(1, 2), (2, 1)]);
+    assert_eq!(PeerRule::AntiKnight.cells(4, 4, 9).len(), 8);
+    assert_eq!(
+        PeerRule::AntiKing.cells(8, 8, 9),
+        vec![(7, 7), (7, 8), (8, 7)]
+    );
+    assert!(PeerRule::AntiKnight.relates((3, 3), (5, 4)));
+    assert!(!PeerRule::AntiKnight.relates((3, 3), (5, 5)));
+    assert!(PeerRule::AntiKing.relates((3, 3), (2, 4)));
+}
+
+#[test]
+fn solves_anti_knight_and_anti_king() {
+    use crate::logic::{self, Step, Technique};
+    use crate::sudoku::SudokuBoard;
+
+    let mut knight = SudokuBoard::from_puzzle_string(
+        "37.......2...6...7...2..........32.................4....5...9...8.3..........6.4.",
+    )
+    .unwrap();
+    SudokuBoard::add_peer_rule(&mut knight, PeerRule::AntiKnight);
+    assert_eq!(SudokuBoard::get_peers(&knight, 4, 4).len(), 28);
+    // The 6 in r2c5 is a knight's move from r1c3.
+    assert_eq!(knight.possible_solutions[0][2], vec![1, 4, 8, 9]);
+    assert!(logic::solve_path(&knight).solved());
+
+    let mut king = SudokuBoard::from_puzzle_string(
+        ".5.......78..4.........6....3...5.4...1...27.9.....3...7.4..6...6.1........9.3..8",
+    )
+    .unwrap();
+    SudokuBoard::add_peer_rule(&mut king, PeerRule::AntiKing);
+    SudokuBoard::add_peer_rule(&mut king, PeerRule::AntiKing);
+    assert_eq!(king.peer_rules.len(), 1);
+    assert!(logic::solve_path(&king).solved());
+
+    let placement = Step {
+        technique: Technique::NakedSingle,
+        units: Vec::new(),
+        cells: vec![(4, 4)],
+        digits: vec![5],
+        placement: Some((4, 4, 5)),
+        eliminations: Vec::new(),
+    };
+    let mut empty = SudokuBoard::new();
+    SudokuBoard::add_peer_rule(&mut empty, PeerRule::AntiKing);
+    logic::apply_step(&mut empty, &placement);
+    assert!(!empty.possible_solutions[3][5].contains(&5));
+    assert!(empty.possible_solutions[2][6].contains(&5));
+}
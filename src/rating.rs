@@ -0,0 +1,204 @@
+use crate::logic::{self, Step, Technique, Unit};
+use crate::sudoku::SudokuBoard;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// The labels NYT prints on its daily puzzles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<Difficulty> {
+        match label {
+            "easy" => Some(Difficulty::Easy),
+            "medium" => Some(Difficulty::Medium),
+            "hard" => Some(Difficulty::Hard),
+            _ => None,
+        }
+    }
+
+    /// Singles only is easy; locked candidates, pairs and X-Wings are medium;
+    /// anything harder, including guessing, is hard.
+    pub fn from_score(score: f32) -> Difficulty {
+        if score <= 2.3 {
+            Difficulty::Easy
+        } else if score <= 3.4 {
+            Difficulty::Medium
+        } else {
+            Difficulty::Hard
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rating {
+    pub difficulty: Difficulty,
+    /// Score of the hardest step on the Sudoku Explainer scale, raised by
+    /// `STEP_WEIGHT` for every further step beyond the easy techniques and by
+    /// `GUESS_WEIGHT` for every guess after the first.
+    pub score: f32,
+    pub hardest: Option<Technique>,
+    pub steps: usize,
+    pub guesses: usize,
+    pub technique_counts: BTreeMap<Technique, usize>,
+    /// False when the puzzle has no solution; the rating then covers the
+    /// steps taken before the contradiction.
+    pub solvable: bool,
+}
+
+/// What each step beyond the easy techniques adds to the score.
+pub const STEP_WEIGHT: f32 = 0.05;
+/// What each guess after the first adds to the score.
+pub const GUESS_WEIGHT: f32 = 0.2;
+
+/// Sudoku Explainer's rating for each technique. A guess sits above every
+/// technique the logic solver knows, where Explainer puts forcing chains.
+pub fn step_score(step: &Step) -> f32 {
+    match step.technique {
+        Technique::HiddenSingle => match step.units.first() {
+            Some(Unit::Box(_)) => 1.2,
+            _ => 1.5,
+        },
+        Technique::NakedSingle => 2.3,
+        Technique::RuleOf45 => 2.0,
+        Technique::Pointing => 2.6,
+        Technique::Claiming => 2.8,
+        Technique::CageCombination => 2.8,
+        Technique::PairElimination => 2.8,
+        Technique::LineElimination => 2.8,
+        Technique::ClueElimination => 2.8,
+        Technique::NakedPair => 3.0,
+        Technique::XWing => 3.2,
+        Technique::HiddenPair => 3.4,
+        Technique::NakedTriple => 3.6,
+        Technique::Swordfish => 3.8,
+        Technique::HiddenTriple => 4.0,
+        Technique::XYWing => 4.2,
+        Technique::Guess => 7.0,
+    }
+}
+
+/// Rates a puzzle by solving it simplest technique first, guessing from the
+/// backtracking solution whenever logic runs dry. The difficulty label comes
+/// from the hardest step alone; step and guess counts only move the score.
+pub fn rate(board: &SudokuBoard) -> Rating {
+    let mut s_board = board.clone();
+    s_board.possible_solutions = SudokuBoard::calc_possible_solutions(&s_board);
+    let mut rating = Rating {
+        difficulty: Difficulty::Easy,
+        score: 0.0,
+        hardest: None,
+        steps: 0,
+        guesses: 0,
+        technique_counts: BTreeMap::new(),
+        solvable: true,
+    };
+    let mut hard_steps: usize = 0;
+
+    while !SudokuBoard::populated(&s_board) {
+        let step = match logic::next_step(&s_board).or_else(|| logic::guess_step(&s_board)) {
+            Some(step) => step,
+            None => {
+                rating.solvable = false;
+                break;
+            }
+        };
+        let score = step_score(&step);
+        if score > rating.score {
+            rating.score = score;
+            rating.hardest = Some(step.technique);
+        }
+        if Difficulty::from_score(score) > Difficulty::Easy {
+            hard_steps += 1;
+        }
+        if step.technique == Technique::Guess {
+            rating.guesses += 1;
+        }
+        rating.steps += 1;
+        *rating.technique_counts.entry(step.technique).or_insert(0) += 1;
+        logic::apply_step(&mut s_board, &step);
+    }
+
+    rating.difficulty = Difficulty::from_score(rating.score);
+    rating.score += STEP_WEIGHT * (hard_steps.saturating_sub(1) as f32)
+        + GUESS_WEIGHT * (rating.guesses.saturating_sub(1) as f32);
+    rating
+}
+
+#[test]
+fn rates_easy_puzzle() {
+    let rating = rate(&SudokuBoard::from_puzzle([
+        [0, 6, 0, 0, 3, 0, 8, 7, 0],
+        [0, 0, 0, 2, 0, 0, 1, 4, 3],
+        [0, 1, 7, 0, 5, 8, 0, 0, 0],
+        [0, 7, 0, 0, 0, 1, 0, 2, 8],
+        [9, 5, 4, 0, 8, 0, 0, 0, 0],
+        [8, 0, 0, 6, 0, 7, 3, 0, 4],
+        [0, 4, 0, 9, 0, 0, 2, 8, 1],
+        [0, 0, 9, 0, 1, 4, 0, 0, 7],
+        [1, 0, 6, 7, 0, 0, 4, 0, 0],
+    ]));
+    assert_eq!(rating.difficulty, Difficulty::Easy);
+    assert_eq!(rating.guesses, 0);
+    assert_eq!(rating.steps, 43);
+    assert!(rating.score <= 2.3);
+    assert!(rating.solvable);
+}
+
+#[test]
+fn rates_harder_puzzle() {
+    let rating = rate(&SudokuBoard::from_puzzle([
+        [0, 3, 0, 8, 0, 7, 0, 0, 5],
+        [0, 0, 0, 0, 0, 5, 0, 0, 3],
+        [0, 0, 0, 6, 0, 0, 1, 0, 0],
+        [6, 0, 0, 4, 0, 0, 2, 0, 0],
+        [2, 0, 0, 0, 0, 0, 4, 8, 9],
+        [0, 8, 0, 0, 0, 0, 0, 3, 0],
+        [0, 0, 2, 7, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 6, 0, 0, 0],
+        [0, 9, 7, 0, 0, 0, 0, 4, 2],
+    ]));
+    assert_eq!(rating.difficulty, Difficulty::Medium);
+    assert_eq!(rating.hardest, Some(Technique::NakedPair));
+    let hard_steps: usize = rating
+        .technique_counts
+        .iter()
+        .filter(|(technique, _)| {
+            !matches!(
+                technique,
+                Technique::HiddenSingle | Technique::NakedSingle | Technique::RuleOf45
+            )
+        })
+        .map(|(_, count)| count)
+        .sum();
+    assert!(hard_steps > 1);
+    let expected = 3.0 + STEP_WEIGHT * (hard_steps - 1) as f32;
+    assert!((rating.score - expected).abs() < 1e-4);
+}
+
+#[test]
+fn counts_guesses_on_empty_grid() {
+    let rating = rate(&SudokuBoard::from_puzzle([[0; 9]; 9]));
+    assert!(rating.guesses > 1);
+    assert_eq!(rating.difficulty, Difficulty::Hard);
+    assert!(rating.score >= 7.0 + GUESS_WEIGHT * (rating.guesses - 1) as f32);
+    assert!(rating.solvable);
+}
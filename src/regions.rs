@@ -0,0 +1,529 @@
+use crate::sudoku::{BoxShape, Grid};
+use std::fmt;
+
+/// The region (box) each cell belongs to. Classic boards use the boxes of
+/// their `BoxShape`; jigsaw boards use any split of the grid into `size`
+/// regions of `size` cells each.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Regions {
+    index: Vec<Vec<usize>>,
+    cells: Vec<Vec<(usize, usize)>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegionError {
+    /// The layout is not a square grid.
+    NotSquare,
+    /// A region number in a map is negative or not below the side length.
+    InvalidRegion(i32),
+    WrongRegionCount {
+        expected: usize,
+        found: usize,
+    },
+    WrongRegionSize {
+        region: usize,
+        cells: usize,
+    },
+    /// An extra unit names a cell outside the board.
+    CellOutOfRange {
+        row: usize,
+        col: usize,
+    },
+    /// An extra unit names the same cell twice.
+    DuplicateCell {
+        row: usize,
+        col: usize,
+    },
+    /// An extra unit has more cells than there are digits.
+    UnitTooLarge {
+        cells: usize,
+    },
+}
+
+impl fmt::Display for RegionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegionError::NotSquare => write!(f, "region layout is not a square grid"),
+            RegionError::InvalidRegion(region) => write!(f, "{} is not a region number", region),
+            RegionError::WrongRegionCount { expected, found } => {
+                write!(f, "expected {} regions but found {}", expected, found)
+            }
+            RegionError::WrongRegionSize { region, cells } => write!(
+                f,
+                "region {} has {} cells instead of one per row",
+                region + 1,
+                cells
+            ),
+            RegionError::CellOutOfRange { row, col } => {
+                write!(f, "r{}c{} is outside the board", row + 1, col + 1)
+            }
+            RegionError::DuplicateCell { row, col } => {
+                write!(f, "r{}c{} appears twice in one unit", row + 1, col + 1)
+            }
+            RegionError::UnitTooLarge { cells } => {
+                write!(f, "a unit of {} cells cannot hold distinct digits", cells)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RegionError {}
+
+impl Regions {
+    /// The usual boxes, numbered across then down.
+    pub fn boxes(box_shape: BoxShape) -> Regions {
+        let size = box_shape.size();
+        let boxes_per_band = size / box_shape.cols;
+        let index = (0..size)
+            .map(|i| {
+                (0..size)
+                    .map(|j| (i / box_shape.rows) * boxes_per_band + j / box_shape.cols)
+                    .collect()
+            })
+            .collect();
+        Regions::from_index(index)
+    }
+
+    /// Regions from a map of region numbers, which must run from 0 to
+    /// `size - 1` with `size` cells each. Regions need not be connected.
+    pub fn from_map(map: Grid) -> Result<Regions, RegionError> {
+        let size = map.len();
+        if map.iter().any(|row| row.len() != size) {
+            return Err(RegionError::NotSquare);
+        }
+        let mut counts = vec![0; size];
+        for value in map.iter().flatten() {
+            match usize::try_from(*value).ok().filter(|region| *region < size) {
+                Some(region) => counts[region] += 1,
+                None => return Err(RegionError::InvalidRegion(*value)),
+            }
+        }
+        if let Some(region) = counts.iter().position(|count| *count != size) {
+            return Err(RegionError::WrongRegionSize {
+                region,
+                cells: counts[region],
+            });
+        }
+        Ok(Regions::from_index(
+            map.iter()
+                .map(|row| row.iter().map(|value| *value as usize).collect())
+                .collect(),
+        ))
+    }
+
+    /// Parses a layout written one character per cell in row-major order,
+    /// e.g. 81 characters for a 9x9 jigsaw. Any characters can label the
+    /// regions; they are numbered in order of first appearance. Whitespace is
+    /// ignored.
+    pub fn parse(layout: &str) -> Result<Regions, RegionError> {
+        let cells: Vec<char> = layout.chars().filter(|c| !c.is_whitespace()).collect();
+        let size = (1..=cells.len())
+            .find(|size| size * size == cells.len())
+            .ok_or(RegionError::NotSquare)?;
+        let mut labels: Vec<char> = Vec::new();
+        let mut map = vec![vec![0; size]; size];
+        for (index, label) in cells.iter().enumerate() {
+            let region = match labels.iter().position(|l| l == label) {
+                Some(region) => region,
+                None => {
+                    labels.push(*label);
+                    labels.len() - 1
+                }
+            };
+            map[index / size][index % size] = region as i32;
+        }
+        if labels.len() != size {
+            return Err(RegionError::WrongRegionCount {
+                expected: size,
+                found: labels.len(),
+            });
+        }
+        Regions::from_map(map)
+    }
+
+    fn from_index(index: Vec<Vec<usize>>) -> Regions {
+        let mut cells = vec![Vec::new(); index.len()];
+        for (i, row) in index.iter().enumerate() {
+            for (j, region) in row.iter().enumerate() {
+                cells[*region].push((i, j));
+            }
+        }
+        Regions { index, cells }
+    }
+
+    pub fn size(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn region(&self, row: usize, col: usize) -> usize {
+        self.index[row][col]
+    }
+
+    /// The cells of `region` in row-major order.
+    pub fn cells(&self, region: usize) -> &[(usize, usize)] {
+        &self.cells[region]
+    }
+
+    /// Writes the layout in the format read by `parse`, labelling regions
+    /// from 1 and then from A.
+    pub fn to_layout_string(&self) -> String {
+        const LABELS: &[u8] = b"123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+        self.index
+            .iter()
+            .flatten()
+            .map(|region| LABELS.get(*region).map_or('?', |label| *label as char))
+            .collect()
+    }
+}
+
+/// Cells grouped by the label they share in a layout.
+pub type CellGroups = Vec<Vec<(usize, usize)>>;
+
+/// Reads a square layout written one character per cell, ignoring
+/// whitespace, and groups the cells by label in order of first appearance;
+/// `.` marks a cell in no group. Gives the side length and the groups, or
+/// `None` if the layout is not square.
+pub fn parse_groups(layout: &str) -> Option<(usize, CellGroups)> {
+    let cells: Vec<char> = layout.chars().filter(|c| !c.is_whitespace()).collect();
+    let size = (1..=cells.len()).find(|size| size * size == cells.len())?;
+    let mut labels: Vec<char> = Vec::new();
+    let mut groups: CellGroups = Vec::new();
+    for (index, label) in cells.iter().enumerate() {
+        if *label == '.' {
+            continue;
+        }
+        let group = match labels.iter().position(|l| l == label) {
+            Some(group) => group,
+            None => {
+                labels.push(*label);
+                groups.push(Vec::new());
+                groups.len() - 1
+            }
+        };
+        groups[group].push((index / size, index % size));
+    }
+    Some((size, groups))
+}
+
+/// What an extra unit is, for naming it in explanations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ExtraKind {
+    MainDiagonal,
+    AntiDiagonal,
+    /// One of the extra boxes of Windoku.
+    Window(usize),
+    /// The cells at the same position within every box.
+    DisjointGroup(usize),
+    /// One of the eight diagonal lines of Argyle Sudoku.
+    Argyle(usize),
+    Girandola,
+    /// A unit given by the user, e.g. with `ExtraUnit::parse`.
+    Custom(usize),
+}
+
+impl fmt::Display for ExtraKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExtraKind::MainDiagonal => write!(f, "Main diagonal"),
+            ExtraKind::AntiDiagonal => write!(f, "Anti-diagonal"),
+            ExtraKind::Window(i) => write!(f, "Window {}", i + 1),
+            ExtraKind::DisjointGroup(i) => write!(f, "Disjoint group {}", i + 1),
+            ExtraKind::Argyle(i) => write!(f, "Argyle line {}", i + 1),
+            ExtraKind::Girandola => write!(f, "Girandola"),
+            ExtraKind::Custom(i) => write!(f, "Extra unit {}", i + 1),
+        }
+    }
+}
+
+/// A unit beyond rows, columns and boxes: no digit repeats in its cells. A
+/// unit with fewer cells than the board's side need not hold every digit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtraUnit {
+    pub kind: ExtraKind,
+    pub cells: Vec<(usize, usize)>,
+}
+
+impl ExtraUnit {
+    /// The two main diagonals of Sudoku X, from the top-left and top-right.
+    pub fn diagonals(size: usize) -> Vec<ExtraUnit> {
+        vec![
+            ExtraUnit {
+                kind: ExtraKind::MainDiagonal,
+                cells: (0..size).map(|i| (i, i)).collect(),
+            },
+            ExtraUnit {
+                kind: ExtraKind::AntiDiagonal,
+                cells: (0..size).map(|i| (i, size - 1 - i)).collect(),
+            },
+        ]
+    }
+
+    /// The windows of Windoku: boxes one cell in from the edge with one
+    /// cell between them, e.g. four 3x3 windows on a classic board.
+    pub fn windows(box_shape: BoxShape) -> Vec<ExtraUnit> {
+        let size = box_shape.size();
+        let starts = |step: usize| {
+            (1..size)
+                .step_by(step + 1)
+                .filter(move |start| start + step < size)
+        };
+        let mut units = Vec::new();
+        for top in starts(box_shape.rows) {
+            for left in starts(box_shape.cols) {
+                let cells = (top..top + box_shape.rows)
+                    .flat_map(|i| (left..left + box_shape.cols).map(move |j| (i, j)))
+                    .collect();
+                units.push(ExtraUnit {
+                    kind: ExtraKind::Window(units.len()),
+                    cells,
+                });
+            }
+        }
+        units
+    }
+
+    /// Disjoint groups: group `k` holds the `k`th cell of every box.
+    pub fn disjoint_groups(box_shape: BoxShape) -> Vec<ExtraUnit> {
+        let size = box_shape.size();
+        (0..size)
+            .map(|group| {
+                let (row, col) = (group / box_shape.cols, group % box_shape.cols);
+                ExtraUnit {
+                    kind: ExtraKind::DisjointGroup(group),
+                    cells: (0..size)
+                        .step_by(box_shape.rows)
+                        .flat_map(|i| (0..size).step_by(box_shape.cols).map(move |j| (i, j)))
+                        .map(|(i, j)| (i + row, j + col))
+                        .collect(),
+                }
+            })
+            .collect()
+    }
+
+    /// The eight diagonal lines of Argyle Sudoku on a classic board, one
+    /// and four cells off each main diagonal.
+    pub fn argyle() -> Vec<ExtraUnit> {
+        let mut lines: Vec<Vec<(usize, usize)>> = Vec::new();
+        for offset in [1, 4] {
+            lines.push((0..9 - offset).map(|i| (i, i + offset)).collect());
+            lines.push((0..9 - offset).map(|i| (i + offset, i)).collect());
+            lines.push((0..9 - offset).map(|i| (i, 8 - offset - i)).collect());
+            lines.push((0..9 - offset).map(|i| (i + offset, 8 - i)).collect());
+        }
+        lines
+            .into_iter()
+            .enumerate()
+            .map(|(i, cells)| ExtraUnit {
+                kind: ExtraKind::Argyle(i),
+                cells,
+            })
+            .collect()
+    }
+
+    /// The nine cells of Girandola on a classic board: the corners, the
+    /// centre and the middle of each box edge facing the centre.
+    pub fn girandola() -> ExtraUnit {
+        ExtraUnit {
+            kind: ExtraKind::Girandola,
+            cells: vec![
+                (0, 0),
+                (0, 8),
+                (1, 4),
+                (4, 1),
+                (4, 4),
+                (4, 7),
+                (7, 4),
+                (8, 0),
+                (8, 8),
+            ],
+        }
+    }
+
+    /// Parses user-defined units from a layout like `Regions::parse`, where
+    /// `.` marks a cell in no unit and every other label one unit, numbered
+    /// in order of first appearance.
+    pub fn parse(layout: &str) -> Result<Vec<ExtraUnit>, RegionError> {
+        let (size, groups) = parse_groups(layout).ok_or(RegionError::NotSquare)?;
+        let units: Vec<ExtraUnit> = groups
+            .into_iter()
+            .enumerate()
+            .map(|(i, cells)| ExtraUnit {
+                kind: ExtraKind::Custom(i),
+                cells,
+            })
+            .collect();
+        for unit in &units {
+            unit.check(size)?;
+        }
+        Ok(units)
+    }
+
+    /// Checks that the unit fits a board of side `size`.
+    pub fn check(&self, size: usize) -> Result<(), RegionError> {
+        if self.cells.len() > size {
+            return Err(RegionError::UnitTooLarge {
+                cells: self.cells.len(),
+            });
+        }
+        for (index, (row, col)) in self.cells.iter().enumerate() {
+            if *row >= size || *col >= size {
+                return Err(RegionError::CellOutOfRange {
+                    row: *row,
+                    col: *col,
+                });
+            }
+            if self.cells[..index].contains(&(*row, *col)) {
+                return Err(RegionError::DuplicateCell {
+                    row: *row,
+                    col: *col,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn parses_jigsaw_layouts() {
+    let layout =
+        "112222333 111222333 111122333 444555566 444555666 444556666 777888999 777888999 777888999";
+    let regions = Regions::parse(layout).unwrap();
+    assert_eq!(regions.size(), 9);
+    assert_eq!(regions.region(0, 2), 1);
+    assert_eq!(regions.region(2, 3), 0);
+    assert_eq!(regions.cells(5).len(), 9);
+    assert_eq!(regions.to_layout_string(), layout.replace(' ', ""));
+    assert_eq!(Regions::parse(&layout.replace(' ', "")).unwrap(), regions);
+
+    assert_eq!(
+        Regions::parse("1123"),
+        Err(RegionError::WrongRegionCount {
+            expected: 2,
+            found: 3
+        })
+    );
+    assert_eq!(
+        Regions::parse("1112"),
+        Err(RegionError::WrongRegionSize {
+            region: 0,
+            cells: 3
+        })
+    );
+    assert_eq!(Regions::parse("11122"), Err(RegionError::NotSquare));
+    assert_eq!(
+        Regions::from_map(vec![vec![0, 0], vec![1, 2]]),
+        Err(RegionError::InvalidRegion(2))
+    );
+    assert_eq!(Regions::boxes(BoxShape::CLASSIC).region(4, 8), 5);
+}
+
+#[test]
+fn builds_extra_units() {
+    let windows = ExtraUnit::windows(BoxShape::CLASSIC);
+    assert_eq!(windows.len(), 4);
+    assert_eq!(windows[0].cells[0], (1, 1));
+    assert_eq!(windows[3].cells[8], (7, 7));
+    assert_eq!(ExtraUnit::windows(BoxShape::new(2, 2)).len(), 1);
+
+    let groups = ExtraUnit::disjoint_groups(BoxShape::CLASSIC);
+    assert_eq!(groups[4].cells[0], (1, 1));
+    assert_eq!(groups[4].cells[8], (7, 7));
+    assert!(groups.iter().all(|group| group.check(9).is_ok()));
+
+    let argyle = ExtraUnit::argyle();
+    assert_eq!(
+        argyle.iter().map(|line| line.cells.len()).sum::<usize>(),
+        52
+    );
+    assert!(argyle.iter().all(|line| line.check(9).is_ok()));
+    assert!(ExtraUnit::girandola().check(9).is_ok());
+
+    let custom = ExtraUnit::parse("ab.. ab.. ..cc ....").unwrap();
+    assert_eq!(custom.len(), 3);
+    assert_eq!(custom[1].cells, vec![(0, 1), (1, 1)]);
+    assert_eq!(custom[2].kind, ExtraKind::Custom(2));
+    assert_eq!(
+        ExtraUnit::parse("aaaa a... .... ...."),
+        Err(RegionError::UnitTooLarge { cells: 5 })
+    );
+    assert_eq!(
+        ExtraUnit::diagonals(9)[0].check(4),
+        Err(RegionError::UnitTooLarge { cells: 9 })
+    );
+}
+
+#[test]
+fn solves_sudoku_x() {
+    use crate::logic::{self, sees, Technique, Unit};
+    use crate::sudoku::SudokuBoard;
+
+    let mut board = SudokuBoard::from_puzzle_string(
+        "2......4118.3......9....7....1...6.4...2......7...............63..5...7.9....3...",
+    )
+    .unwrap();
+    assert_eq!(board.possible_solutions[3][3], vec![7, 8, 9]);
+    SudokuBoard::add_extra_units(&mut board, ExtraUnit::diagonals(9)).unwrap();
+    assert_eq!(board.possible_solutions[3][3], vec![9]);
+    assert!(sees(&board, (0, 0), (8, 8)));
+    assert!(!sees(&board, (0, 0), (8, 7)));
+
+    let path = logic::solve_path(&board);
+    assert!(path.solved());
+    let diagonal = path
+        .steps
+        .iter()
+        .find(|step| matches!(step.units[..], [Unit::Extra(..)]))
+        .unwrap();
+    assert_eq!(diagonal.technique, Technique::HiddenSingle);
+    assert_eq!(
+        diagonal.units,
+        vec![Unit::Extra(1, ExtraKind::AntiDiagonal)]
+    );
+    assert_eq!(diagonal.placement, Some((4, 4, 3)));
+}
+
+#[test]
+fn solves_windoku() {
+    use crate::logic::{self, Unit};
+    use crate::sudoku::SudokuBoard;
+
+    let mut board = SudokuBoard::from_puzzle_string(
+        "..4....6.....89......2.3...4..1.6..........2.......3.46.......8....1..5..2.......",
+    )
+    .unwrap();
+    assert_eq!(board.possible_solutions[1][1], vec![1, 3, 5, 6, 7]);
+    SudokuBoard::add_extra_units(&mut board, ExtraUnit::windows(BoxShape::CLASSIC)).unwrap();
+    assert_eq!(board.possible_solutions[1][1], vec![3, 5, 6, 7]);
+
+    let path = logic::solve_path(&board);
+    assert!(path.solved());
+    let window = path
+        .steps
+        .iter()
+        .find(|step| matches!(step.units[..], [Unit::Extra(..)]))
+        .unwrap();
+    assert_eq!(window.units, vec![Unit::Extra(0, ExtraKind::Window(0))]);
+    assert_eq!(window.placement, Some((1, 3, 4)));
+}
+
+#[test]
+fn places_only_correct_digits_on_argyle_lines() {
+    use crate::logic::{self, Unit};
+    use crate::sudoku::SudokuBoard;
+
+    // Argyle lines are shorter than nine cells, so a digit with one place on
+    // a line need not go there.
+    let mut board = SudokuBoard::from_puzzle_string(
+        "...................................................4.7..........6...4831.12873..5",
+    )
+    .unwrap();
+    SudokuBoard::add_extra_units(&mut board, ExtraUnit::argyle()).unwrap();
+    assert!(!Unit::Extra(0, ExtraKind::Argyle(0)).is_complete(&board));
+    let solution = SudokuBoard::find_solutions(&board, 2);
+    assert_eq!(solution.len(), 1);
+    for step in logic::solve_path(&board).steps {
+        if let Some((row, col, value)) = step.placement {
+            assert_eq!(solution[0][row][col], value);
+        }
+    }
+}
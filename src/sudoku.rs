@@ -1,22 +1,144 @@
+use crate::cages::{Cage, CageError};
+use crate::lines::{Line, LineError};
+use crate::outside::{ClueError, OutsideClue};
+use crate::pairs::{PairConstraint, PairError};
+use crate::parity::{ParityCell, ParityError};
+use crate::peers::PeerRule;
+use crate::regions::{ExtraUnit, RegionError, Regions};
+use crate::symbols::SymbolSet;
 use std::clone::Clone;
+use std::fmt;
 
+#[allow(clippy::enum_variant_names)]
 pub enum RowGroup {
     RowTop,
     RowMiddle,
     RowBottom,
 }
 
+#[allow(clippy::enum_variant_names)]
 pub enum ColGroup {
     ColLeft,
     ColMiddle,
     ColRight,
 }
 
-#[derive(Default, Clone)]
+/// The largest side a board can have; candidate sets are bitmasks over
+/// `1 << value` in a `u32`.
+pub const MAX_SIZE: usize = 25;
+
+/// Cell values in row-major order, 0 for empty. Square, with side `size()`.
+pub type Grid = Vec<Vec<i32>>;
+
+/// Why a board could not be built from a shape, regions or a grid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoardError {
+    /// A side of 0 or larger than `MAX_SIZE`.
+    BadSize { size: usize },
+    /// The grid is not `size` rows of `size` cells.
+    WrongDimensions { size: usize },
+    /// A cell value that is neither 0 nor a digit of the board.
+    BadValue { row: usize, col: usize, value: i32 },
+}
+
+impl fmt::Display for BoardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BoardError::BadSize { size } => write!(
+                f,
+                "a board of size {} is not between 1 and {}",
+                size, MAX_SIZE
+            ),
+            BoardError::WrongDimensions { size } => {
+                write!(f, "grid is not {} rows of {} cells", size, size)
+            }
+            BoardError::BadValue { row, col, value } => {
+                write!(f, "{} in r{}c{} is not a digit", value, row + 1, col + 1)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BoardError {}
+
+/// The shape of a box: `rows` tall and `cols` wide. A board's side is
+/// `rows * cols`, e.g. 2x3 boxes for 6x6 or 3x4 boxes for 12x12.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BoxShape {
+    pub rows: usize,
+    pub cols: usize,
+}
+
+impl BoxShape {
+    pub const CLASSIC: BoxShape = BoxShape { rows: 3, cols: 3 };
+
+    pub fn new(rows: usize, cols: usize) -> BoxShape {
+        BoxShape { rows, cols }
+    }
+
+    /// The usual shape for a side length: as square as possible, wider than
+    /// tall. `None` for sides with no such split, like 7, and for sides
+    /// above `MAX_SIZE`.
+    pub fn for_size(size: usize) -> Option<BoxShape> {
+        if size > MAX_SIZE {
+            return None;
+        }
+        let rows = (1..=size)
+            .take_while(|rows| rows * rows <= size)
+            .filter(|rows| size.is_multiple_of(*rows))
+            .last()?;
+        if rows == 1 && size > 1 {
+            return None;
+        }
+        Some(BoxShape::new(rows, size / rows))
+    }
+
+    pub fn size(&self) -> usize {
+        self.rows * self.cols
+    }
+
+    pub fn transposed(&self) -> BoxShape {
+        BoxShape::new(self.cols, self.rows)
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct SudokuBoard {
-    pub puzzle: [[i32; 9]; 9],
-    pub possible_solutions: [[Vec<i32>; 9]; 9],
+    pub puzzle: Grid,
+    pub possible_solutions: Vec<Vec<Vec<i32>>>,
     pub needs_solving: bool,
+    /// `None` on jigsaw boards, which have regions instead of rectangular
+    /// boxes.
+    pub box_shape: Option<BoxShape>,
+    pub regions: Regions,
+    /// Units beyond rows, columns and boxes, such as the diagonals of Sudoku X.
+    pub extra_units: Vec<ExtraUnit>,
+    /// Killer cages; no cell is in more than one.
+    pub cages: Vec<Cage>,
+    /// Global rules such as anti-knight that add peers to every cell.
+    pub peer_rules: Vec<PeerRule>,
+    /// Relations between the digits of two cells, such as Kropki dots.
+    pub pair_constraints: Vec<PairConstraint>,
+    /// Thermometers, arrows and other lines.
+    pub lines: Vec<Line>,
+    /// Sandwich, skyscraper and little killer clues around the grid.
+    pub outside_clues: Vec<OutsideClue>,
+    /// Cells marked to hold an even or an odd digit.
+    pub parity_cells: Vec<ParityCell>,
+}
+
+impl Default for SudokuBoard {
+    fn default() -> SudokuBoard {
+        SudokuBoard::with_shape(BoxShape::CLASSIC)
+    }
+}
+
+fn check_size(size: usize) -> Result<(), BoardError> {
+    if (1..=MAX_SIZE).contains(&size) {
+        Ok(())
+    } else {
+        Err(BoardError::BadSize { size })
+    }
 }
 
 impl SudokuBoard {
@@ -24,21 +146,404 @@ impl SudokuBoard {
         SudokuBoard::default()
     }
 
-    pub fn from_puzzle(puzzle: [[i32; 9]; 9]) -> SudokuBoard {
-        let mut new_board = SudokuBoard::new();
-        new_board.puzzle = puzzle;
-        new_board.possible_solutions = SudokuBoard::calc_possible_solutions(&new_board);
-        new_board
+    /// An empty board with boxes of `box_shape`. Panics if the board would be
+    /// empty or larger than `MAX_SIZE`; see `try_with_shape`.
+    pub fn with_shape(box_shape: BoxShape) -> SudokuBoard {
+        SudokuBoard::try_with_shape(box_shape).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// An empty board with boxes of `box_shape`, or an error if the board
+    /// would be empty or larger than `MAX_SIZE`.
+    pub fn try_with_shape(box_shape: BoxShape) -> Result<SudokuBoard, BoardError> {
+        let size = box_shape.rows.saturating_mul(box_shape.cols);
+        check_size(size)?;
+        Ok(SudokuBoard {
+            box_shape: Some(box_shape),
+            ..SudokuBoard::empty(Regions::boxes(box_shape))
+        })
+    }
+
+    /// An empty jigsaw board, whose boxes are the given regions. Panics if the
+    /// board would be larger than `MAX_SIZE`; see `try_with_regions`.
+    pub fn with_regions(regions: Regions) -> SudokuBoard {
+        SudokuBoard::try_with_regions(regions).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// An empty jigsaw board, or an error if it would be empty or larger
+    /// than `MAX_SIZE`.
+    pub fn try_with_regions(regions: Regions) -> Result<SudokuBoard, BoardError> {
+        check_size(regions.size())?;
+        Ok(SudokuBoard::empty(regions))
+    }
+
+    fn empty(regions: Regions) -> SudokuBoard {
+        let size = regions.size();
+        SudokuBoard {
+            puzzle: vec![vec![0; size]; size],
+            possible_solutions: vec![vec![Vec::new(); size]; size],
+            needs_solving: false,
+            box_shape: None,
+            regions,
+            extra_units: Vec::new(),
+            cages: Vec::new(),
+            peer_rules: Vec::new(),
+            pair_constraints: Vec::new(),
+            lines: Vec::new(),
+            outside_clues: Vec::new(),
+            parity_cells: Vec::new(),
+        }
+    }
+
+    /// Builds a board from a literal grid, using the usual box shape for its
+    /// size (see `BoxShape::for_size`).
+    pub fn from_puzzle<const N: usize>(puzzle: [[i32; N]; N]) -> SudokuBoard {
+        let box_shape = BoxShape::for_size(N).expect("no standard box shape for this size");
+        SudokuBoard::from_grid(puzzle.iter().map(|row| row.to_vec()).collect(), box_shape)
+    }
+
+    /// Panics unless `puzzle` fits boxes of `box_shape`; see `try_from_grid`.
+    pub fn from_grid(puzzle: Grid, box_shape: BoxShape) -> SudokuBoard {
+        SudokuBoard::try_from_grid(puzzle, box_shape).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// A board with boxes of `box_shape` holding `puzzle`, or an error if
+    /// the grid is not square with the boxes' side or holds values that are
+    /// not digits.
+    pub fn try_from_grid(puzzle: Grid, box_shape: BoxShape) -> Result<SudokuBoard, BoardError> {
+        SudokuBoard::filled(SudokuBoard::try_with_shape(box_shape)?, puzzle)
+    }
+
+    /// Panics unless `puzzle` fits `regions`; see `try_from_jigsaw`.
+    pub fn from_jigsaw(puzzle: Grid, regions: Regions) -> SudokuBoard {
+        SudokuBoard::try_from_jigsaw(puzzle, regions).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// A jigsaw board holding `puzzle`, checked as for `try_from_grid`.
+    pub fn try_from_jigsaw(puzzle: Grid, regions: Regions) -> Result<SudokuBoard, BoardError> {
+        SudokuBoard::filled(SudokuBoard::try_with_regions(regions)?, puzzle)
+    }
+
+    fn filled(mut board: SudokuBoard, puzzle: Grid) -> Result<SudokuBoard, BoardError> {
+        let size = board.size();
+        if puzzle.len() != size || puzzle.iter().any(|row| row.len() != size) {
+            return Err(BoardError::WrongDimensions { size });
+        }
+        for (row, values) in puzzle.iter().enumerate() {
+            for (col, value) in values.iter().enumerate() {
+                if !(0..=size as i32).contains(value) {
+                    return Err(BoardError::BadValue {
+                        row,
+                        col,
+                        value: *value,
+                    });
+                }
+            }
+        }
+        board.puzzle = puzzle;
+        board.possible_solutions = SudokuBoard::calc_possible_solutions(&board);
+        Ok(board)
+    }
+
+    pub fn size(&self) -> usize {
+        self.regions.size()
+    }
+
+    /// Adds units that must not repeat a digit and recalculates candidates.
+    /// Nothing is added if any unit does not fit the board.
+    pub fn add_extra_units(
+        board: &mut SudokuBoard,
+        units: Vec<ExtraUnit>,
+    ) -> Result<(), RegionError> {
+        for unit in &units {
+            unit.check(board.size())?;
+        }
+        board.extra_units.extend(units);
+        board.possible_solutions = SudokuBoard::calc_possible_solutions(board);
+        Ok(())
+    }
+
+    /// Adds killer cages and recalculates candidates. Nothing is added if
+    /// any cage does not fit the board or overlaps another cage.
+    pub fn add_cages(board: &mut SudokuBoard, cages: Vec<Cage>) -> Result<(), CageError> {
+        let mut taken: Vec<(usize, usize)> = board
+            .cages
+            .iter()
+            .flat_map(|cage| cage.cells.clone())
+            .collect();
+        for cage in &cages {
+            cage.check(board.size())?;
+            if let Some((row, col)) = cage.cells.iter().find(|cell| taken.contains(cell)) {
+                return Err(CageError::Overlap {
+                    row: *row,
+                    col: *col,
+                });
+            }
+            taken.extend(&cage.cells);
+        }
+        board.cages.extend(cages);
+        board.possible_solutions = SudokuBoard::calc_possible_solutions(board);
+        Ok(())
+    }
+
+    /// Adds a global peer rule, once, and recalculates candidates.
+    pub fn add_peer_rule(board: &mut SudokuBoard, rule: PeerRule) {
+        if !board.peer_rules.contains(&rule) {
+            board.peer_rules.push(rule);
+        }
+        board.possible_solutions = SudokuBoard::calc_possible_solutions(board);
+    }
+
+    /// Adds pairwise constraints and recalculates candidates. Nothing is
+    /// added if any pair does not fit the board.
+    pub fn add_pair_constraints(
+        board: &mut SudokuBoard,
+        pairs: Vec<PairConstraint>,
+    ) -> Result<(), PairError> {
+        for pair in &pairs {
+            pair.check(board.size())?;
+        }
+        board.pair_constraints.extend(pairs);
+        board.possible_solutions = SudokuBoard::calc_possible_solutions(board);
+        Ok(())
+    }
+
+    /// Whether `value` in (row, col) satisfies every pairwise constraint
+    /// whose other cell is filled.
+    pub fn pairs_allow(board: &SudokuBoard, row: usize, col: usize, value: i32) -> bool {
+        board.pair_constraints.iter().all(|pair| {
+            pair.partner((row, col)).is_none_or(|(i, j)| {
+                board.puzzle[i][j] == 0 || pair.relation.allows(value, board.puzzle[i][j])
+            })
+        })
+    }
+
+    /// Adds lines and recalculates candidates. Nothing is added if any line
+    /// does not fit the board.
+    pub fn add_lines(board: &mut SudokuBoard, lines: Vec<Line>) -> Result<(), LineError> {
+        for line in &lines {
+            line.check(board.size())?;
+        }
+        board.lines.extend(lines);
+        board.possible_solutions = SudokuBoard::calc_possible_solutions(board);
+        Ok(())
+    }
+
+    /// The digits along a line, with 0 for empty cells.
+    pub fn get_line_values(board: &SudokuBoard, line: &Line) -> Vec<i32> {
+        line.cells
+            .iter()
+            .map(|(i, j)| board.puzzle[*i][*j])
+            .collect()
+    }
+
+    /// Whether every line through (row, col) can still be completed with
+    /// `value` there.
+    pub fn lines_allow(board: &SudokuBoard, row: usize, col: usize, value: i32) -> bool {
+        board.lines.iter().all(|line| {
+            match line.cells.iter().position(|cell| *cell == (row, col)) {
+                Some(position) => {
+                    let mut values = SudokuBoard::get_line_values(board, line);
+                    values[position] = value;
+                    line.allows(&values, board.size())
+                }
+                None => true,
+            }
+        })
+    }
+
+    /// Adds clues around the grid and recalculates candidates. Nothing is
+    /// added if any clue does not fit the board.
+    pub fn add_outside_clues(
+        board: &mut SudokuBoard,
+        clues: Vec<OutsideClue>,
+    ) -> Result<(), ClueError> {
+        for clue in &clues {
+            clue.check(board.size())?;
+        }
+        board.outside_clues.extend(clues);
+        board.possible_solutions = SudokuBoard::calc_possible_solutions(board);
+        Ok(())
+    }
+
+    /// The digits a clue looks along, nearest first, with 0 for empty cells.
+    pub fn get_clue_values(board: &SudokuBoard, clue: &OutsideClue) -> Vec<i32> {
+        clue.cells(board.size())
+            .iter()
+            .map(|(i, j)| board.puzzle[*i][*j])
+            .collect()
+    }
+
+    /// Whether every outside clue looking along (row, col) can still be
+    /// satisfied with `value` there.
+    pub fn clues_allow(board: &SudokuBoard, row: usize, col: usize, value: i32) -> bool {
+        board.outside_clues.iter().all(|clue| {
+            let cells = clue.cells(board.size());
+            match cells.iter().position(|cell| *cell == (row, col)) {
+                Some(position) => {
+                    let mut values = SudokuBoard::get_clue_values(board, clue);
+                    values[position] = value;
+                    clue.allows(&values, board.size())
+                }
+                None => true,
+            }
+        })
     }
 
-    pub fn calc_possible_solutions(board: &SudokuBoard) -> [[Vec<i32>; 9]; 9] {
-        let mut ret_value: [[Vec<i32>; 9]; 9] = Default::default();
-        for i in 0..9 {
-            for j in 0..9 {
+    /// Adds even and odd marks and recalculates candidates. Nothing is added
+    /// if any mark is off the board.
+    pub fn add_parity_cells(
+        board: &mut SudokuBoard,
+        cells: Vec<ParityCell>,
+    ) -> Result<(), ParityError> {
+        for cell in &cells {
+            cell.check(board.size())?;
+        }
+        board.parity_cells.extend(cells);
+        board.possible_solutions = SudokuBoard::calc_possible_solutions(board);
+        Ok(())
+    }
+
+    /// Whether `value` in (row, col) fits the cell's even or odd marks.
+    pub fn parity_allows(board: &SudokuBoard, row: usize, col: usize, value: i32) -> bool {
+        board
+            .parity_cells
+            .iter()
+            .all(|mark| mark.cell != (row, col) || mark.parity.allows(value))
+    }
+
+    /// Whether `a` and `b` are different cells that may not hold the same
+    /// value: they share a row, column, box, extra unit or cage, or a peer
+    /// rule relates them.
+    pub fn is_peer(board: &SudokuBoard, a: (usize, usize), b: (usize, usize)) -> bool {
+        a != b
+            && (a.0 == b.0
+                || a.1 == b.1
+                || SudokuBoard::get_box_index(board, a.0, a.1)
+                    == SudokuBoard::get_box_index(board, b.0, b.1)
+                || board
+                    .extra_units
+                    .iter()
+                    .any(|unit| unit.cells.contains(&a) && unit.cells.contains(&b))
+                || SudokuBoard::get_cage_index(board, a.0, a.1)
+                    .is_some_and(|cage| board.cages[cage].cells.contains(&b))
+                || board.peer_rules.iter().any(|rule| rule.relates(a, b)))
+    }
+
+    /// Every peer of (row, col) (see `is_peer`), in row-major order.
+    pub fn get_peers(board: &SudokuBoard, row: usize, col: usize) -> Vec<(usize, usize)> {
+        let size = board.size();
+        let mut peers: Vec<(usize, usize)> = (0..size).flat_map(|i| [(row, i), (i, col)]).collect();
+        peers.extend(SudokuBoard::get_box_cells(board, row, col));
+        for unit in SudokuBoard::get_extra_units_for_cell(board, row, col) {
+            peers.extend(&board.extra_units[unit].cells);
+        }
+        if let Some(cage) = SudokuBoard::get_cage_index(board, row, col) {
+            peers.extend(&board.cages[cage].cells);
+        }
+        for rule in &board.peer_rules {
+            peers.extend(rule.cells(row, col, size));
+        }
+        peers.retain(|cell| *cell != (row, col));
+        peers.sort_unstable();
+        peers.dedup();
+        peers
+    }
+
+    /// Index into `cages` of the cage containing (row, col), if any.
+    pub fn get_cage_index(board: &SudokuBoard, row: usize, col: usize) -> Option<usize> {
+        board
+            .cages
+            .iter()
+            .position(|cage| cage.cells.contains(&(row, col)))
+    }
+
+    /// The digits the empty cells of a cage can still take: those in a
+    /// combination for its sum that holds every digit already placed in it.
+    pub fn get_cage_values(board: &SudokuBoard, cage: usize) -> Vec<i32> {
+        let placed: Vec<i32> = board.cages[cage]
+            .cells
+            .iter()
+            .map(|(i, j)| board.puzzle[*i][*j])
+            .filter(|value| *value != 0)
+            .collect();
+        let mut values: Vec<i32> = board.cages[cage]
+            .combinations(board.size())
+            .into_iter()
+            .filter(|combination| placed.iter().all(|value| combination.contains(value)))
+            .flatten()
+            .filter(|value| !placed.contains(value))
+            .collect();
+        values.sort_unstable();
+        values.dedup();
+        values
+    }
+
+    /// Indices into `extra_units` of the units containing (row, col).
+    pub fn get_extra_units_for_cell(board: &SudokuBoard, row: usize, col: usize) -> Vec<usize> {
+        (0..board.extra_units.len())
+            .filter(|unit| board.extra_units[*unit].cells.contains(&(row, col)))
+            .collect()
+    }
+
+    /// Parses the common one-line format: the cells in row-major order, with
+    /// `0` or `.` for empty cells. Whitespace is ignored and the side length
+    /// is taken from the number of cells. Values above 9 are written as
+    /// letters from `A`; see `SymbolSet::for_size`.
+    pub fn from_puzzle_string(line: &str) -> Option<SudokuBoard> {
+        let cells = line.chars().filter(|c| !c.is_whitespace()).count();
+        let size = (1..=cells).find(|size| size * size == cells)?;
+        SudokuBoard::from_symbol_string(line, &SymbolSet::for_size(size))
+    }
+
+    /// Parses the one-line format written with `symbols`. The side length is
+    /// the size of the symbol set.
+    pub fn from_symbol_string(line: &str, symbols: &SymbolSet) -> Option<SudokuBoard> {
+        let size = symbols.size();
+        let cells: Vec<char> = line.chars().filter(|c| !c.is_whitespace()).collect();
+        if cells.len() != size * size {
+            return None;
+        }
+        let mut puzzle = vec![vec![0; size]; size];
+        for (index, cell) in cells.iter().enumerate() {
+            puzzle[index / size][index % size] = symbols.value(*cell)?;
+        }
+        Some(SudokuBoard::from_grid(puzzle, BoxShape::for_size(size)?))
+    }
+
+    /// Writes the puzzle in the one-line format, with `.` for empty cells.
+    pub fn to_puzzle_string(puzzle: &Grid) -> String {
+        SudokuBoard::to_symbol_string(puzzle, &SymbolSet::for_size(puzzle.len()))
+    }
+
+    pub fn to_symbol_string(puzzle: &Grid, symbols: &SymbolSet) -> String {
+        puzzle
+            .iter()
+            .flatten()
+            .map(|value| symbols.symbol(*value))
+            .collect()
+    }
+
+    #[allow(clippy::needless_range_loop)]
+    pub fn calc_possible_solutions(board: &SudokuBoard) -> Vec<Vec<Vec<i32>>> {
+        let size = board.size();
+        let mut ret_value: Vec<Vec<Vec<i32>>> = vec![vec![Vec::new(); size]; size];
+        for i in 0..size {
+            for j in 0..size {
                 if board.puzzle[i][j] == 0 {
                     ret_value[i][j] = SudokuBoard::get_inverse_values(
-                        SudokuBoard::get_existing_values(&board.puzzle, i, j),
+                        SudokuBoard::get_existing_values(board, i, j),
+                        size,
                     );
+                    if let Some(cage) = SudokuBoard::get_cage_index(board, i, j) {
+                        let cage_values = SudokuBoard::get_cage_values(board, cage);
+                        ret_value[i][j].retain(|value| cage_values.contains(value));
+                    }
+                    ret_value[i][j].retain(|value| {
+                        SudokuBoard::parity_allows(board, i, j, *value)
+                            && SudokuBoard::pairs_allow(board, i, j, *value)
+                            && SudokuBoard::lines_allow(board, i, j, *value)
+                            && SudokuBoard::clues_allow(board, i, j, *value)
+                    });
                 } else {
                     ret_value[i][j] = Default::default();
                 }
@@ -47,6 +552,7 @@ impl SudokuBoard {
         ret_value
     }
 
+    /// Three cells of a box row; boxes must be at least three wide.
     pub fn get_puzzle_row(
         board: &SudokuBoard,
         box_coord: (i32, i32),
@@ -58,10 +564,7 @@ impl SudokuBoard {
             RowGroup::RowBottom => 2,
         };
 
-        let natural_boundaries = SudokuBoard::get_boundaries_for_cell(
-            (box_coord.0 * 3) as usize,
-            (box_coord.1 * 3) as usize,
-        );
+        let natural_boundaries = SudokuBoard::get_box_origin(board, box_coord);
         let x = natural_boundaries.0 + row_offset;
         (
             board.puzzle[x][natural_boundaries.1],
@@ -70,6 +573,7 @@ impl SudokuBoard {
         )
     }
 
+    #[allow(clippy::vec_init_then_push)]
     pub fn get_solution_row(&self, box_coord: (i32, i32), row: RowGroup) -> Vec<&Vec<i32>> {
         let row_offset = match row {
             RowGroup::RowTop => 0,
@@ -77,10 +581,7 @@ impl SudokuBoard {
             RowGroup::RowBottom => 2,
         };
 
-        let natural_boundaries = SudokuBoard::get_boundaries_for_cell(
-            (box_coord.0 * 3) as usize,
-            (box_coord.1 * 3) as usize,
-        );
+        let natural_boundaries = SudokuBoard::get_box_origin(self, box_coord);
         let x = natural_boundaries.0 + row_offset;
         let mut ret_vec: Vec<&Vec<i32>> = Default::default();
         ret_vec.push(&self.possible_solutions[x][natural_boundaries.1]);
@@ -90,6 +591,7 @@ impl SudokuBoard {
         ret_vec
     }
 
+    #[allow(clippy::vec_init_then_push)]
     pub fn get_solution_col(&self, box_coord: (i32, i32), col: ColGroup) -> Vec<&Vec<i32>> {
         let col_offset = match col {
             ColGroup::ColLeft => 0,
@@ -97,10 +599,7 @@ impl SudokuBoard {
             ColGroup::ColRight => 2,
         };
 
-        let natural_boundaries = SudokuBoard::get_boundaries_for_cell(
-            (box_coord.0 * 3) as usize,
-            (box_coord.1 * 3) as usize,
-        );
+        let natural_boundaries = SudokuBoard::get_box_origin(self, box_coord);
         let y = natural_boundaries.1 + col_offset;
         let mut ret_vec: Vec<&Vec<i32>> = Default::default();
         ret_vec.push(&self.possible_solutions[natural_boundaries.0][y]);
@@ -110,46 +609,82 @@ impl SudokuBoard {
         ret_vec
     }
 
-    pub fn get_boundaries_for_cell(row: usize, col: usize) -> (usize, usize) {
-        let mut row_boundary: usize = 0;
-        let mut col_boundary: usize = 0;
-        if row < 3 {
-            row_boundary = 0;
-        }
-        if (3..6).contains(&row) {
-            row_boundary = 3;
-        }
-        if row >= 6 {
-            row_boundary = 6;
-        }
-        if col < 3 {
-            col_boundary = 0;
-        }
-        if (3..6).contains(&col) {
-            col_boundary = 3;
-        }
-        if col >= 6 {
-            col_boundary = 6;
-        }
+    /// The top-left corner of the box or region containing (row, col); for
+    /// irregular regions, the corner of the rectangle around it.
+    pub fn get_boundaries_for_cell(board: &SudokuBoard, row: usize, col: usize) -> (usize, usize) {
+        let cells = SudokuBoard::get_box_cells(board, row, col);
+        let row_boundary = cells.iter().map(|cell| cell.0).min().unwrap_or(row);
+        let col_boundary = cells.iter().map(|cell| cell.1).min().unwrap_or(col);
         (row_boundary, col_boundary)
     }
 
-    pub fn print_puzzle(puzzle: &[[i32; 9]; 9]) {
-        for row in 0..puzzle.len() {
-            if row % 3 == 0 {
-                SudokuBoard::print_dash_line();
+    /// The top-left corner of the box `box_coord` bands down and stacks
+    /// across. Jigsaw boards are addressed as if they had the usual box shape
+    /// for their size, taking the region found at that box's corner.
+    fn get_box_origin(board: &SudokuBoard, box_coord: (i32, i32)) -> (usize, usize) {
+        let shape = board
+            .box_shape
+            .or_else(|| BoxShape::for_size(board.size()))
+            .expect("no box layout for this size");
+        SudokuBoard::get_boundaries_for_cell(
+            board,
+            box_coord.0 as usize * shape.rows,
+            box_coord.1 as usize * shape.cols,
+        )
+    }
+
+    /// Index of the box or region containing (row, col). Boxes count across
+    /// then down.
+    pub fn get_box_index(board: &SudokuBoard, row: usize, col: usize) -> usize {
+        board.regions.region(row, col)
+    }
+
+    /// The cells of the box or region containing (row, col).
+    pub fn get_box_cells(board: &SudokuBoard, row: usize, col: usize) -> &[(usize, usize)] {
+        board.regions.cells(board.regions.region(row, col))
+    }
+
+    /// Prints a bare grid, boxed in the usual shape for its size; sides with
+    /// no usual shape, like 7, get a rule under every row.
+    pub fn print_puzzle(puzzle: &Grid) {
+        let size = puzzle.len();
+        let mut board = SudokuBoard::with_shape(
+            BoxShape::for_size(size).unwrap_or_else(|| BoxShape::new(1, size)),
+        );
+        board.puzzle = puzzle.clone();
+        SudokuBoard::print_board(&board);
+    }
+
+    /// Prints a board with its own boxes or regions and outside clues.
+    pub fn print_board(board: &SudokuBoard) {
+        SudokuBoard::print_board_with_symbols(board, &SymbolSet::for_size(board.size()));
+    }
+
+    pub fn print_board_with_symbols(board: &SudokuBoard, symbols: &SymbolSet) {
+        let boxed = board
+            .box_shape
+            .is_some_and(|shape| board.regions == Regions::boxes(shape));
+        if !boxed || !board.outside_clues.is_empty() {
+            SudokuBoard::print_regions(board, symbols);
+            return;
+        }
+        for row in 0..board.puzzle.len() {
+            if row == 0 || board.regions.region(row - 1, 0) != board.regions.region(row, 0) {
+                SudokuBoard::print_dash_line(board);
             }
-            let arr_size = puzzle[row].len();
+            let arr_size = board.puzzle[row].len();
             for item in 0..arr_size {
-                if item % 3 == 0 {
+                if item == 0
+                    || board.regions.region(row, item - 1) != board.regions.region(row, item)
+                {
                     print!("| ");
                 }
 
-                let cell_value = puzzle[row][item];
+                let cell_value = board.puzzle[row][item];
                 if cell_value == 0 {
                     print!("  ");
                 } else {
-                    print!("{} ", puzzle[row][item]);
+                    print!("{} ", symbols.symbol(cell_value));
                 }
 
                 if item == arr_size - 1 {
@@ -157,41 +692,110 @@ impl SudokuBoard {
                 }
             }
         }
-        SudokuBoard::print_dash_line();
+        SudokuBoard::print_dash_line(board);
     }
 
-    pub fn print_dash_line() {
-        let mut counter = 0;
-        while counter < 25 {
-            print!("-");
-            counter += 1;
+    /// Draws irregular regions: `|` and `---` mark where neighbouring cells
+    /// belong to different regions. Outside clues go in a margin around the
+    /// grid.
+    fn print_regions(board: &SudokuBoard, symbols: &SymbolSet) {
+        let size = board.size();
+        let labels = SudokuBoard::clue_labels(board);
+        let width = labels
+            .iter()
+            .map(|row| row[0].chars().count())
+            .max()
+            .unwrap_or(0);
+        let margin = |label: &str| match width {
+            0 => String::new(),
+            _ => format!("{:>width$} ", label, width = width),
+        };
+        let print_clue_row = |row: usize| {
+            if labels[row].iter().all(|label| label.is_empty()) {
+                return;
+            }
+            let mut line = margin(&labels[row][0]);
+            line.push(' ');
+            for label in &labels[row][1..=size] {
+                line.push_str(&format!("{:^3} ", label));
+            }
+            line.push_str(&labels[row][size + 1]);
+            println!("{}", line.trim_end());
+        };
+        print_clue_row(0);
+        for row in 0..=size {
+            let mut line = margin("");
+            line.push('+');
+            for col in 0..size {
+                let border = row == 0
+                    || row == size
+                    || board.regions.region(row - 1, col) != board.regions.region(row, col);
+                line.push_str(if border { "---+" } else { "   +" });
+            }
+            println!("{}", line);
+            if row == size {
+                break;
+            }
+            let mut line = margin(&labels[row + 1][0]);
+            line.push('|');
+            for col in 0..size {
+                let cell_value = board.puzzle[row][col];
+                line.push(' ');
+                line.push(if cell_value == 0 {
+                    ' '
+                } else {
+                    symbols.symbol(cell_value)
+                });
+                line.push(' ');
+                let border = col == size - 1
+                    || board.regions.region(row, col) != board.regions.region(row, col + 1);
+                line.push(if border { '|' } else { ' ' });
+            }
+            line.push(' ');
+            line.push_str(&labels[row + 1][size + 1]);
+            println!("{}", line.trim_end());
         }
-        println!();
+        print_clue_row(size + 1);
     }
 
-    pub fn get_existing_values(puzzle: &[[i32; 9]; 9], row: usize, col: usize) -> Vec<i32> {
-        let mut values = Vec::new();
-        for i in 0..9 {
-            let row_val = puzzle[row][i];
-            let col_val = puzzle[i][col];
-            if row_val >= 1 {
-                values.push(row_val);
-            }
-            if col_val >= 1 {
-                values.push(col_val);
+    /// The outside clues as drawn around the grid, indexed from the top-left
+    /// corner one cell outside it. Clues drawn in the same place are joined
+    /// with `/`.
+    fn clue_labels(board: &SudokuBoard) -> Vec<Vec<String>> {
+        let size = board.size();
+        let mut labels = vec![vec![String::new(); size + 2]; size + 2];
+        for clue in &board.outside_clues {
+            let (row, col) = clue.position(size);
+            let label = &mut labels[(row + 1) as usize][(col + 1) as usize];
+            if !label.is_empty() {
+                label.push('/');
             }
+            label.push_str(&clue.label(size));
         }
+        labels
+    }
 
-        let xy_boundary = SudokuBoard::get_boundaries_for_cell(row, col);
-
-        for i in 0..3 {
-            for j in 0..3 {
-                let box_val = puzzle[i + xy_boundary.0][j + xy_boundary.1];
-                if box_val >= 1 {
-                    values.push(box_val);
-                }
-            }
+    pub fn print_dash_line(board: &SudokuBoard) {
+        let boxes_across = (0..board.size())
+            .filter(|col| {
+                *col == 0 || board.regions.region(0, col - 1) != board.regions.region(0, *col)
+            })
+            .count();
+        let line_length = board.size() * 2 + boxes_across * 2 + 1;
+        let mut counter = 0;
+        while counter < line_length {
+            print!("-");
+            counter += 1;
         }
+        println!();
+    }
+
+    pub fn get_existing_values(board: &SudokuBoard, row: usize, col: usize) -> Vec<i32> {
+        let mut values: Vec<i32> = SudokuBoard::get_peers(board, row, col)
+            .iter()
+            .map(|(i, j)| board.puzzle[*i][*j])
+            .filter(|value| *value >= 1)
+            .collect();
 
         values.sort_unstable();
         values.dedup();
@@ -199,10 +803,11 @@ impl SudokuBoard {
         values
     }
 
-    pub fn get_inverse_values(values: Vec<i32>) -> Vec<i32> {
+    /// The values from 1 to `size` missing from `values`.
+    pub fn get_inverse_values(values: Vec<i32>, size: usize) -> Vec<i32> {
         let mut ret_values = Vec::new();
 
-        for i in 1..10 {
+        for i in 1..=size as i32 {
             let mut i_found = false;
             for elem in &values {
                 if elem == &(i) {
@@ -216,30 +821,15 @@ impl SudokuBoard {
         ret_values
     }
 
-    pub fn get_box_values(
-        possible_values: &[[Vec<i32>; 9]; 9],
-        row: usize,
-        col: usize,
-    ) -> Vec<i32> {
+    pub fn get_box_values(board: &SudokuBoard, row: usize, col: usize) -> Vec<i32> {
+        let possible_values = &board.possible_solutions;
         let mut ret_vals: Vec<i32> = Default::default();
 
-        let xy_boundary = SudokuBoard::get_boundaries_for_cell(row, col);
-
         for elem in &possible_values[row][col] {
             let mut valid_value = true;
-            for i in 0..3 {
-                for j in 0..3 {
-                    let box_vals = &possible_values[i + xy_boundary.0][j + xy_boundary.1];
-                    if box_vals.contains(elem)
-                        && !(i + xy_boundary.0 == row && j + xy_boundary.1 == col)
-                    {
-                        valid_value = false;
-                    }
-                    if !valid_value {
-                        break;
-                    }
-                }
-                if !valid_value {
+            for (i, j) in SudokuBoard::get_box_cells(board, row, col) {
+                if possible_values[*i][*j].contains(elem) && !(*i == row && *j == col) {
+                    valid_value = false;
                     break;
                 }
             }
@@ -258,7 +848,7 @@ impl SudokuBoard {
         col: usize,
     ) -> Vec<i32> {
         let mut new_vec: Vec<i32> = Default::default();
-        for value in 0..10 {
+        for value in 0..=board.size() as i32 {
             if board.possible_solutions[row][col].contains(&value) && value != possible_value {
                 new_vec.push(value);
             }
@@ -268,196 +858,96 @@ impl SudokuBoard {
     }
 
     pub fn solve_deterministic(s_board: &mut SudokuBoard) {
+        let size = s_board.size();
         s_board.needs_solving = true;
         while s_board.needs_solving {
             s_board.needs_solving = false;
             s_board.possible_solutions = SudokuBoard::calc_possible_solutions(s_board);
             let mut removable_vals: Vec<(usize, usize, i32)> = Default::default();
-            for cube_x in 0..3 {
-                for cube_y in 0..3 {
-                    let top_row =
-                        SudokuBoard::get_solution_row(s_board, (cube_x, cube_y), RowGroup::RowTop);
-                    let middle_row = SudokuBoard::get_solution_row(
-                        s_board,
-                        (cube_x, cube_y),
-                        RowGroup::RowMiddle,
-                    );
-                    let bottom_row = SudokuBoard::get_solution_row(
-                        s_board,
-                        (cube_x, cube_y),
-                        RowGroup::RowBottom,
-                    );
-                    let left_col = SudokuBoard::get_solution_col(
-                        s_board,
-                        (cube_x, cube_y),
-                        ColGroup::ColLeft,
-                    );
-                    let mid_col = SudokuBoard::get_solution_col(
-                        s_board,
-                        (cube_x, cube_y),
-                        ColGroup::ColMiddle,
-                    );
-                    let right_col = SudokuBoard::get_solution_col(
-                        s_board,
-                        (cube_x, cube_y),
-                        ColGroup::ColRight,
-                    );
-
-                    let mut top_found_in_cube;
-                    let mut t_row: Vec<&i32> = top_row.into_iter().flatten().collect();
-                    t_row.sort_unstable();
-                    t_row.dedup();
-                    for possible_value in &t_row {
-                        top_found_in_cube = false;
-                        let possible_value = **possible_value as i32;
-                        for cell_index in 0..3 {
-                            if middle_row[cell_index].contains(&possible_value)
-                                || bottom_row[cell_index].contains(&possible_value)
-                            {
-                                top_found_in_cube = true;
-                                break;
-                            }
-                        }
-                        if !top_found_in_cube {
-                            for i in 0..9 {
-                                let x = (cube_x * 3) as usize;
-                                let end_y = ((cube_y * 3) + 3) as usize;
-                                let start_y = (cube_y * 3) as usize;
-                                if (i > 0 && i < start_y) || (i >= end_y && i <= 8) {
-                                    removable_vals.push((x as usize, i as usize, possible_value));
+            //Jigsaw regions have no box rows or columns, so only singles apply
+            if let Some(shape) = s_board.box_shape {
+                for cube_x in 0..shape.cols {
+                    for cube_y in 0..shape.rows {
+                        let box_rows: Vec<Vec<&Vec<i32>>> = (0..shape.rows)
+                            .map(|row| {
+                                let x = cube_x * shape.rows + row;
+                                (0..shape.cols)
+                                    .map(|cell_index| {
+                                        &s_board.possible_solutions[x]
+                                            [cube_y * shape.cols + cell_index]
+                                    })
+                                    .collect()
+                            })
+                            .collect();
+                        let box_cols: Vec<Vec<&Vec<i32>>> = (0..shape.cols)
+                            .map(|col| {
+                                let y = cube_y * shape.cols + col;
+                                (0..shape.rows)
+                                    .map(|cell_index| {
+                                        &s_board.possible_solutions
+                                            [cube_x * shape.rows + cell_index][y]
+                                    })
+                                    .collect()
+                            })
+                            .collect();
+
+                        for (row, box_row) in box_rows.iter().enumerate() {
+                            let mut found_in_cube;
+                            let mut r_vals: Vec<&i32> = box_row.iter().copied().flatten().collect();
+                            r_vals.sort_unstable();
+                            r_vals.dedup();
+                            for possible_value in &r_vals {
+                                found_in_cube = false;
+                                let possible_value = **possible_value;
+                                for (other, other_row) in box_rows.iter().enumerate() {
+                                    if other != row
+                                        && other_row
+                                            .iter()
+                                            .any(|cell| cell.contains(&possible_value))
+                                    {
+                                        found_in_cube = true;
+                                        break;
+                                    }
                                 }
-                            }
-                        }
-                    }
-                    //Mid row
-                    let mut mid_found_in_cube;
-                    let mut m_row: Vec<&i32> = middle_row.into_iter().flatten().collect();
-                    m_row.sort_unstable();
-                    m_row.dedup();
-                    for possible_value in &m_row {
-                        mid_found_in_cube = false;
-                        let possible_value = **possible_value as i32;
-                        for cell_index in 0..3 {
-                            if t_row.contains(&&possible_value)
-                                || bottom_row[cell_index].contains(&possible_value)
-                            {
-                                mid_found_in_cube = true;
-                                break;
-                            }
-                        }
-                        if !mid_found_in_cube {
-                            for i in 0..9 {
-                                let x = ((cube_x * 3) + 1) as usize;
-                                let end_y = ((cube_y * 3) + 3) as usize;
-                                let start_y = (cube_y * 3) as usize;
-                                if (i > 0 && i < start_y) || (i >= end_y && i <= 8) {
-                                    removable_vals.push((x as usize, i as usize, possible_value));
+                                if !found_in_cube {
+                                    for i in 0..size {
+                                        let x = cube_x * shape.rows + row;
+                                        let end_y = (cube_y * shape.cols) + shape.cols;
+                                        let start_y = cube_y * shape.cols;
+                                        if (i > 0 && i < start_y) || (i >= end_y && i < size) {
+                                            removable_vals.push((x, i, possible_value));
+                                        }
+                                    }
                                 }
                             }
                         }
-                    }
-                    //Bot row
-                    let mut bot_found_in_cube;
-                    let mut b_row: Vec<&i32> = bottom_row.into_iter().flatten().collect();
-                    b_row.sort_unstable();
-                    b_row.dedup();
-                    for possible_value in &b_row {
-                        bot_found_in_cube = false;
-                        let possible_value = **possible_value as i32;
-                        for _cell_index in 0..3 {
-                            if t_row.contains(&&possible_value) || m_row.contains(&&possible_value)
-                            {
-                                bot_found_in_cube = true;
-                                break;
-                            }
-                        }
-                        if !bot_found_in_cube {
-                            for i in 0..9 {
-                                let x = ((cube_x * 3) + 2) as usize;
-                                let end_y = ((cube_y * 3) + 3) as usize;
-                                let start_y = (cube_y * 3) as usize;
-                                if (i > 0 && i < start_y) || (i >= end_y && i <= 8) {
-                                    removable_vals.push((x as usize, i as usize, possible_value));
+                        for (col, box_col) in box_cols.iter().enumerate() {
+                            let mut found_in_cube;
+                            let mut c_vals: Vec<&i32> = box_col.iter().copied().flatten().collect();
+                            c_vals.sort_unstable();
+                            c_vals.dedup();
+                            for possible_value in &c_vals {
+                                found_in_cube = false;
+                                let possible_value = **possible_value;
+                                for (other, other_col) in box_cols.iter().enumerate() {
+                                    if other != col
+                                        && other_col
+                                            .iter()
+                                            .any(|cell| cell.contains(&possible_value))
+                                    {
+                                        found_in_cube = true;
+                                        break;
+                                    }
                                 }
-                            }
-                        }
-                    }
-                    //left col
-                    let mut left_found_in_cube;
-                    let mut l_col: Vec<&i32> = left_col.into_iter().flatten().collect();
-                    l_col.sort_unstable();
-                    l_col.dedup();
-                    for possible_value in &l_col {
-                        let possible_value = **possible_value;
-                        left_found_in_cube = false;
-                        for cell_index in 0..3 {
-                            if right_col[cell_index].contains(&possible_value)
-                                || mid_col[cell_index].contains(&possible_value)
-                            {
-                                left_found_in_cube = true;
-                                break;
-                            }
-                        }
-                        if !left_found_in_cube {
-                            for i in 0..9 {
-                                let y = (cube_y * 3) as usize;
-                                let end_x = ((cube_x * 3) + 3) as usize;
-                                let start_x = (cube_x * 3) as usize;
-                                if (i > 0 && i < start_x) || (i >= end_x && i <= 8) {
-                                    removable_vals.push((i as usize, y as usize, possible_value));
-                                }
-                            }
-                        }
-                    }
-                    //right col
-                    let mut right_found_in_cube;
-                    let mut r_col: Vec<&i32> = right_col.into_iter().flatten().collect();
-                    r_col.sort_unstable();
-                    r_col.dedup();
-                    for possible_value in &r_col {
-                        let possible_value = **possible_value;
-                        right_found_in_cube = false;
-                        for cell_index in 0..3 {
-                            if l_col.contains(&&possible_value)
-                                || mid_col[cell_index].contains(&possible_value)
-                            {
-                                right_found_in_cube = true;
-                                break;
-                            }
-                        }
-                        if !right_found_in_cube {
-                            for i in 0..9 {
-                                let y = ((cube_y * 3) + 2) as usize;
-                                let end_x = ((cube_x * 3) + 3) as usize;
-                                let start_x = (cube_x * 3) as usize;
-                                if (i > 0 && i < start_x) || (i >= end_x && i <= 8) {
-                                    removable_vals.push((i as usize, y as usize, possible_value));
-                                }
-                            }
-                        }
-                    }
-                    //mid col
-                    let mut midc_found_in_cube;
-                    let mut m_col: Vec<&i32> = mid_col.into_iter().flatten().collect();
-                    m_col.sort_unstable();
-                    m_col.dedup();
-                    for possible_value in &m_col {
-                        let possible_value = **possible_value;
-                        midc_found_in_cube = false;
-                        for _cell_index in 0..3 {
-                            if l_col.contains(&&possible_value) || r_col.contains(&&possible_value)
-                            {
-                                midc_found_in_cube = true;
-                                break;
-                            }
-                        }
-                        if !midc_found_in_cube {
-                            for i in 0..9 {
-                                let y = ((cube_y * 3) + 1) as usize;
-                                let end_x = ((cube_x * 3) + 3) as usize;
-                                let start_x = (cube_x * 3) as usize;
-                                if (i > 0 && i < start_x) || (i >= end_x && i <= 8) {
-                                    removable_vals.push((i as usize, y as usize, possible_value));
+                                if !found_in_cube {
+                                    for i in 0..size {
+                                        let y = cube_y * shape.cols + col;
+                                        let end_x = (cube_x * shape.rows) + shape.rows;
+                                        let start_x = cube_x * shape.rows;
+                                        if (i > 0 && i < start_x) || (i >= end_x && i < size) {
+                                            removable_vals.push((i, y, possible_value));
+                                        }
+                                    }
                                 }
                             }
                         }
@@ -465,25 +955,20 @@ impl SudokuBoard {
                 }
             }
 
-            for i in 0..removable_vals.len() {
-                s_board.possible_solutions[removable_vals[i].0][removable_vals[i].1] =
-                    SudokuBoard::remove_possible_value_from_cell(
-                        s_board,
-                        removable_vals[i].2,
-                        removable_vals[i].0,
-                        removable_vals[i].1,
-                    );
+            for (row, col, value) in removable_vals {
+                s_board.possible_solutions[row][col] =
+                    SudokuBoard::remove_possible_value_from_cell(s_board, value, row, col);
             }
 
-            for i in 0..9 {
-                for j in 0..9 {
+            for i in 0..size {
+                for j in 0..size {
                     if s_board.puzzle[i][j] == 0 {
                         if s_board.possible_solutions[i][j].len() == 1 {
                             s_board.needs_solving = true;
                             s_board.puzzle[i][j] = s_board.possible_solutions[i][j][0];
                         } else {
                             let possible_values_contextual: Vec<i32> =
-                                SudokuBoard::get_box_values(&s_board.possible_solutions, i, j);
+                                SudokuBoard::get_box_values(s_board, i, j);
                             if possible_values_contextual.len() == 1 {
                                 s_board.needs_solving = true;
                                 s_board.puzzle[i][j] = possible_values_contextual[0];
@@ -500,8 +985,8 @@ impl SudokuBoard {
         greedy_number: i32,
         greed_level: usize,
     ) -> SudokuBoard {
-        for i in 0..9 {
-            for j in 0..9 {
+        for i in 0..s_board.size() {
+            for j in 0..s_board.size() {
                 if s_board.possible_solutions[i][j].len() <= greed_level
                     && s_board.possible_solutions[i][j].contains(&greedy_number)
                 {
@@ -517,8 +1002,99 @@ impl SudokuBoard {
         s_board.clone()
     }
 
+    /// Depth-first search over the puzzle's empty cells, trying the cell with
+    /// the fewest options first. Returns `None` if the puzzle has no solution.
+    pub fn solve_backtracking(board: &SudokuBoard) -> Option<SudokuBoard> {
+        let mut solutions = SudokuBoard::find_solutions(board, 1);
+        solutions.pop().map(|solution| {
+            let mut solved = board.clone();
+            solved.puzzle = solution;
+            solved.possible_solutions = SudokuBoard::calc_possible_solutions(&solved);
+            solved.needs_solving = false;
+            solved
+        })
+    }
+
+    /// Counts solutions, stopping once `limit` are found; a limit of 2 is
+    /// enough to tell whether a puzzle is unique.
+    pub fn count_solutions(board: &SudokuBoard, limit: usize) -> usize {
+        SudokuBoard::find_solutions(board, limit).len()
+    }
+
+    /// Solutions of the board's givens; candidates on the board are ignored.
+    pub fn find_solutions(board: &SudokuBoard, limit: usize) -> Vec<Grid> {
+        let size = board.size();
+        let mut solutions = Vec::new();
+        let mut working = board.puzzle.clone();
+        let mut state = SearchState::new(board);
+        for i in 0..size {
+            for j in 0..size {
+                let value = board.puzzle[i][j];
+                if value == 0 {
+                    continue;
+                }
+                if value < 1 || value > size as i32 {
+                    return solutions;
+                }
+                let bit = 1 << value;
+                if state.options(board, &working, i, j) & bit == 0 {
+                    return solutions;
+                }
+                state.toggle(board, i, j, bit);
+            }
+        }
+        SudokuBoard::search(board, &mut working, &mut state, limit, &mut solutions);
+        solutions
+    }
+
+    fn search(
+        board: &SudokuBoard,
+        puzzle: &mut Grid,
+        state: &mut SearchState,
+        limit: usize,
+        solutions: &mut Vec<Grid>,
+    ) {
+        let size = board.size();
+        let mut best: Option<(usize, usize, u32)> = None;
+        for i in 0..size {
+            for j in 0..size {
+                if puzzle[i][j] != 0 {
+                    continue;
+                }
+                let options = state.options(board, puzzle, i, j);
+                if options == 0 {
+                    return;
+                }
+                if best.is_none_or(|(_, _, o)| options.count_ones() < o.count_ones()) {
+                    best = Some((i, j, options));
+                }
+            }
+        }
+        let (i, j, options) = match best {
+            Some(cell) => cell,
+            None => {
+                solutions.push(puzzle.clone());
+                return;
+            }
+        };
+        for value in 1..=size as i32 {
+            let bit = 1 << value;
+            if options & bit == 0 {
+                continue;
+            }
+            puzzle[i][j] = value;
+            state.toggle(board, i, j, bit);
+            SudokuBoard::search(board, puzzle, state, limit, solutions);
+            puzzle[i][j] = 0;
+            state.toggle(board, i, j, bit);
+            if solutions.len() >= limit {
+                return;
+            }
+        }
+    }
+
     pub fn populated(s_board: &SudokuBoard) -> bool {
-        for row in s_board.puzzle {
+        for row in &s_board.puzzle {
             if row.contains(&0) {
                 return false;
             }
@@ -530,86 +1106,330 @@ impl SudokuBoard {
         SudokuBoard::validate_rows(board)
             && SudokuBoard::validate_columns(board)
             && SudokuBoard::validate_boxes(board)
+            && SudokuBoard::validate_extra_units(board)
+            && SudokuBoard::validate_cages(board)
+            && SudokuBoard::validate_peer_rules(board)
+            && SudokuBoard::validate_pairs(board)
+            && SudokuBoard::validate_lines(board)
+            && SudokuBoard::validate_outside_clues(board)
+            && SudokuBoard::validate_parity_cells(board)
+    }
+
+    /// Whether `values` holds values from 1 to `size` at most once each; a
+    /// full group of `size` values then holds each exactly once.
+    fn validate_group(values: &[i32], size: usize) -> bool {
+        let mut validation = vec![false; size];
+        for value in values {
+            if *value < 1 || *value > size as i32 {
+                return false;
+            }
+            let indexer = (*value - 1) as usize;
+            if !validation[indexer] {
+                validation[indexer] = true;
+            } else {
+                return false;
+            }
+        }
+        true
     }
 
     fn validate_rows(board: &SudokuBoard) -> bool {
         //Rows will be easy;
-        for i in 0..9 {
-            let mut row_validation: [bool; 9] = [
-                false, false, false, false, false, false, false, false, false,
-            ];
-            for j in 0..9 {
-                let indexer = (board.puzzle[i][j] - 1) as usize;
-                if !row_validation[indexer] {
-                    row_validation[indexer] = true;
-                } else {
-                    return false;
-                }
+        board
+            .puzzle
+            .iter()
+            .all(|row| SudokuBoard::validate_group(row, board.size()))
+    }
+
+    fn validate_columns(board: &SudokuBoard) -> bool {
+        for i in 0..board.size() {
+            let col: Vec<i32> = board.puzzle.iter().map(|row| row[i]).collect();
+            if !SudokuBoard::validate_group(&col, board.size()) {
+                return false;
             }
         }
 
         true
     }
 
-    fn validate_columns(board: &SudokuBoard) -> bool {
-        for i in 0..9 {
-            let mut col_validation: [bool; 9] = [
-                false, false, false, false, false, false, false, false, false,
-            ];
-            for j in 0..9 {
-                let indexer = (board.puzzle[j][i] - 1) as usize;
-                if !col_validation[indexer] {
-                    col_validation[indexer] = true;
-                } else {
-                    return false;
-                }
+    fn validate_boxes(board: &SudokuBoard) -> bool {
+        for region in 0..board.size() {
+            let cube: Vec<i32> = board
+                .regions
+                .cells(region)
+                .iter()
+                .map(|(i, j)| board.puzzle[*i][*j])
+                .collect();
+            if !SudokuBoard::validate_group(&cube, board.size()) {
+                return false;
             }
         }
 
         true
     }
 
-    fn validate_boxes(board: &SudokuBoard) -> bool {
-        for i in 0..3 {
-            for j in 0..3 {
-                let mut cube: Vec<i32> = Default::default();
-                let top_row = SudokuBoard::get_puzzle_row(board, (i, j), RowGroup::RowTop);
-                let mid_row = SudokuBoard::get_puzzle_row(board, (i, j), RowGroup::RowMiddle);
-                let bot_row = SudokuBoard::get_puzzle_row(board, (i, j), RowGroup::RowBottom);
-
-                cube.push(top_row.0);
-                cube.push(top_row.1);
-                cube.push(top_row.2);
-                cube.push(mid_row.0);
-                cube.push(mid_row.1);
-                cube.push(mid_row.2);
-                cube.push(bot_row.0);
-                cube.push(bot_row.1);
-                cube.push(bot_row.2);
-
-                for i in 0..9 {
-                    let mut cube_validation: [bool; 9] = [
-                        false, false, false, false, false, false, false, false, false,
-                    ];
-
-                    let indexer = (cube[i] - 1) as usize;
-                    if !cube_validation[indexer] {
-                        cube_validation[indexer] = true;
-                    } else {
-                        return false;
+    fn validate_extra_units(board: &SudokuBoard) -> bool {
+        board.extra_units.iter().all(|unit| {
+            let values: Vec<i32> = unit
+                .cells
+                .iter()
+                .map(|(i, j)| board.puzzle[*i][*j])
+                .collect();
+            SudokuBoard::validate_group(&values, board.size())
+        })
+    }
+
+    fn validate_peer_rules(board: &SudokuBoard) -> bool {
+        let size = board.size();
+        (0..size).all(|i| {
+            (0..size).all(|j| {
+                board.peer_rules.iter().all(|rule| {
+                    rule.cells(i, j, size)
+                        .iter()
+                        .all(|(r, c)| board.puzzle[*r][*c] != board.puzzle[i][j])
+                })
+            })
+        })
+    }
+
+    fn validate_lines(board: &SudokuBoard) -> bool {
+        board.lines.iter().all(|line| {
+            let values = SudokuBoard::get_line_values(board, line);
+            !values.contains(&0) && line.allows(&values, board.size())
+        })
+    }
+
+    fn validate_outside_clues(board: &SudokuBoard) -> bool {
+        board.outside_clues.iter().all(|clue| {
+            let values = SudokuBoard::get_clue_values(board, clue);
+            !values.contains(&0) && clue.allows(&values, board.size())
+        })
+    }
+
+    fn validate_parity_cells(board: &SudokuBoard) -> bool {
+        board
+            .parity_cells
+            .iter()
+            .all(|mark| mark.parity.allows(board.puzzle[mark.cell.0][mark.cell.1]))
+    }
+
+    fn validate_pairs(board: &SudokuBoard) -> bool {
+        board.pair_constraints.iter().all(|pair| {
+            pair.relation.allows(
+                board.puzzle[pair.a.0][pair.a.1],
+                board.puzzle[pair.b.0][pair.b.1],
+            )
+        })
+    }
+
+    fn validate_cages(board: &SudokuBoard) -> bool {
+        board.cages.iter().all(|cage| {
+            let values: Vec<i32> = cage
+                .cells
+                .iter()
+                .map(|(i, j)| board.puzzle[*i][*j])
+                .collect();
+            SudokuBoard::validate_group(&values, board.size())
+                && values.iter().sum::<i32>() == cage.sum
+        })
+    }
+}
+
+/// The digits placed in each unit during `find_solutions`, with what is
+/// known up front about the units and cages each cell is in.
+struct SearchState {
+    /// Bit `v` of row/column/box/extra unit/cage mask `i` is set when `v` is
+    /// placed in unit `i`.
+    masks: Vec<Vec<u32>>,
+    cell_units: Vec<Vec<Vec<usize>>>,
+    cell_cages: Vec<Vec<Option<usize>>>,
+    /// Cells related to each cell by the board's peer rules.
+    rule_peers: Vec<Vec<Vec<(usize, usize)>>>,
+    /// Indices into `pair_constraints` of the pairs on each cell.
+    cell_pairs: Vec<Vec<Vec<usize>>>,
+    /// Indices into `lines` of the lines through each cell.
+    cell_lines: Vec<Vec<Vec<usize>>>,
+    /// The cells each outside clue looks along, and indices into that list
+    /// of the clues looking along each cell.
+    clue_cells: Vec<Vec<(usize, usize)>>,
+    cell_clues: Vec<Vec<Vec<usize>>>,
+    /// The digit sets each cage can hold, as masks.
+    cage_combinations: Vec<Vec<u32>>,
+    /// The digits each cell's own marks allow, such as only even digits.
+    cell_digits: Vec<Vec<u32>>,
+}
+
+impl SearchState {
+    fn new(board: &SudokuBoard) -> SearchState {
+        let size = board.size();
+        let mut cell_units = vec![vec![Vec::new(); size]; size];
+        for (unit, extra) in board.extra_units.iter().enumerate() {
+            for (i, j) in &extra.cells {
+                cell_units[*i][*j].push(unit);
+            }
+        }
+        let mut cell_cages = vec![vec![None; size]; size];
+        for (cage, killer) in board.cages.iter().enumerate() {
+            for (i, j) in &killer.cells {
+                cell_cages[*i][*j] = Some(cage);
+            }
+        }
+        let cage_combinations = board
+            .cages
+            .iter()
+            .map(|cage| {
+                cage.combinations(size)
+                    .iter()
+                    .map(|digits| digits.iter().fold(0, |mask, value| mask | 1 << value))
+                    .collect()
+            })
+            .collect();
+        let rule_peers = (0..size)
+            .map(|i| {
+                (0..size)
+                    .map(|j| {
+                        board
+                            .peer_rules
+                            .iter()
+                            .flat_map(|rule| rule.cells(i, j, size))
+                            .collect()
+                    })
+                    .collect()
+            })
+            .collect();
+        let mut cell_pairs = vec![vec![Vec::new(); size]; size];
+        for (index, pair) in board.pair_constraints.iter().enumerate() {
+            cell_pairs[pair.a.0][pair.a.1].push(index);
+            cell_pairs[pair.b.0][pair.b.1].push(index);
+        }
+        let mut cell_lines = vec![vec![Vec::new(); size]; size];
+        for (index, line) in board.lines.iter().enumerate() {
+            for (i, j) in &line.cells {
+                cell_lines[*i][*j].push(index);
+            }
+        }
+        let clue_cells: Vec<Vec<(usize, usize)>> = board
+            .outside_clues
+            .iter()
+            .map(|clue| clue.cells(size))
+            .collect();
+        let mut cell_clues = vec![vec![Vec::new(); size]; size];
+        for (index, cells) in clue_cells.iter().enumerate() {
+            for (i, j) in cells {
+                cell_clues[*i][*j].push(index);
+            }
+        }
+        let all_values: u32 = ((1u32 << (size + 1)) - 1) & !1;
+        let mut cell_digits = vec![vec![all_values; size]; size];
+        for mark in &board.parity_cells {
+            let (i, j) = mark.cell;
+            cell_digits[i][j] &= (1..=size as i32)
+                .filter(|value| mark.parity.allows(*value))
+                .fold(0, |mask, value| mask | 1 << value);
+        }
+        SearchState {
+            masks: vec![
+                vec![0; size],
+                vec![0; size],
+                vec![0; size],
+                vec![0; board.extra_units.len()],
+                vec![0; board.cages.len()],
+            ],
+            cell_units,
+            cell_cages,
+            rule_peers,
+            cell_pairs,
+            cell_lines,
+            clue_cells,
+            cell_clues,
+            cage_combinations,
+            cell_digits,
+        }
+    }
+
+    /// The digits (i, j) can take given the digits placed so far.
+    fn options(&self, board: &SudokuBoard, puzzle: &Grid, i: usize, j: usize) -> u32 {
+        let size = board.size();
+        let all_values: u32 = ((1u32 << (size + 1)) - 1) & !1;
+        let b = SudokuBoard::get_box_index(board, i, j);
+        let used = self.cell_units[i][j].iter().fold(
+            self.masks[0][i] | self.masks[1][j] | self.masks[2][b],
+            |used, unit| used | self.masks[3][*unit],
+        );
+        let used = self.rule_peers[i][j]
+            .iter()
+            .fold(used, |used, (r, c)| used | 1 << puzzle[*r][*c]);
+        let allowed = match self.cell_cages[i][j] {
+            Some(cage) => {
+                let placed = self.masks[4][cage];
+                self.cage_combinations[cage]
+                    .iter()
+                    .filter(|combination| *combination & placed == placed)
+                    .fold(0, |allowed, combination| allowed | (combination & !placed))
+            }
+            None => all_values,
+        };
+        let allowed = self.cell_pairs[i][j]
+            .iter()
+            .fold(allowed, |allowed, index| {
+                let pair = &board.pair_constraints[*index];
+                let (r, c) = pair.partner((i, j)).unwrap();
+                match puzzle[r][c] {
+                    0 => allowed,
+                    partner => (1..=size as i32)
+                        .filter(|value| !pair.relation.allows(*value, partner))
+                        .fold(allowed, |allowed, value| allowed & !(1 << value)),
+                }
+            });
+        let mut options = !used & allowed & self.cell_digits[i][j];
+        for index in &self.cell_lines[i][j] {
+            let line = &board.lines[*index];
+            let position = line.cells.iter().position(|cell| *cell == (i, j)).unwrap();
+            let mut values: Vec<i32> = line.cells.iter().map(|(r, c)| puzzle[*r][*c]).collect();
+            for value in 1..=size as i32 {
+                if options & 1 << value != 0 {
+                    values[position] = value;
+                    if !line.allows(&values, size) {
+                        options &= !(1 << value);
                     }
                 }
             }
         }
+        for index in &self.cell_clues[i][j] {
+            let clue = &board.outside_clues[*index];
+            let cells = &self.clue_cells[*index];
+            let position = cells.iter().position(|cell| *cell == (i, j)).unwrap();
+            let mut values: Vec<i32> = cells.iter().map(|(r, c)| puzzle[*r][*c]).collect();
+            for value in 1..=size as i32 {
+                if options & 1 << value != 0 {
+                    values[position] = value;
+                    if !clue.allows(&values, size) {
+                        options &= !(1 << value);
+                    }
+                }
+            }
+        }
+        options
+    }
 
-        true
+    /// Sets `bit` in every unit mask of (i, j), or clears it if it was set.
+    fn toggle(&mut self, board: &SudokuBoard, i: usize, j: usize, bit: u32) {
+        let b = SudokuBoard::get_box_index(board, i, j);
+        self.masks[0][i] ^= bit;
+        self.masks[1][j] ^= bit;
+        self.masks[2][b] ^= bit;
+        for unit in &self.cell_units[i][j] {
+            self.masks[3][*unit] ^= bit;
+        }
+        if let Some(cage) = self.cell_cages[i][j] {
+            self.masks[4][cage] ^= bit;
+        }
     }
 }
 
 #[test]
 pub fn test_top_row_puzzle_access() {
-    let mut board: SudokuBoard = SudokuBoard::new();
-    board.puzzle = [
+    let board = SudokuBoard::from_puzzle([
         [0, 0, 4, 0, 0, 0, 6, 0, 0],
         [2, 7, 0, 0, 0, 0, 0, 9, 0],
         [0, 0, 0, 2, 8, 0, 0, 0, 0],
@@ -619,7 +1439,7 @@ pub fn test_top_row_puzzle_access() {
         [0, 8, 0, 3, 0, 4, 0, 0, 0],
         [9, 5, 0, 0, 0, 0, 0, 8, 0],
         [0, 0, 0, 0, 1, 0, 0, 0, 0],
-    ];
+    ]);
 
     let test_row = SudokuBoard::get_puzzle_row(&board, (0, 0), RowGroup::RowTop);
     assert_eq!(test_row.0, 0);
@@ -629,8 +1449,7 @@ pub fn test_top_row_puzzle_access() {
 
 #[test]
 pub fn test_mid_row_puzzle_access() {
-    let mut board: SudokuBoard = SudokuBoard::new();
-    board.puzzle = [
+    let board = SudokuBoard::from_puzzle([
         [0, 0, 4, 0, 0, 0, 6, 0, 0],
         [2, 7, 0, 0, 0, 0, 0, 9, 0],
         [0, 0, 0, 2, 8, 0, 0, 0, 0],
@@ -640,7 +1459,7 @@ pub fn test_mid_row_puzzle_access() {
         [0, 8, 0, 3, 0, 4, 0, 0, 0],
         [9, 5, 0, 0, 0, 0, 0, 8, 0],
         [0, 0, 0, 0, 1, 0, 0, 0, 0],
-    ];
+    ]);
 
     let test_row = SudokuBoard::get_puzzle_row(&board, (0, 0), RowGroup::RowMiddle);
     assert_eq!(test_row.0, 2);
@@ -650,8 +1469,7 @@ pub fn test_mid_row_puzzle_access() {
 
 #[test]
 fn test_bot_row_puzzle_access() {
-    let mut board: SudokuBoard = SudokuBoard::new();
-    board.puzzle = [
+    let board = SudokuBoard::from_puzzle([
         [0, 0, 4, 0, 0, 0, 6, 0, 0],
         [2, 7, 0, 0, 0, 0, 0, 9, 0],
         [1, 2, 3, 2, 8, 0, 0, 0, 0],
@@ -661,7 +1479,7 @@ fn test_bot_row_puzzle_access() {
         [0, 8, 0, 3, 0, 4, 0, 0, 0],
         [9, 5, 0, 0, 0, 0, 0, 8, 0],
         [0, 0, 0, 0, 1, 0, 0, 0, 0],
-    ];
+    ]);
 
     let test_row = SudokuBoard::get_puzzle_row(&board, (0, 0), RowGroup::RowBottom);
     assert_eq!(test_row.0, 1);
@@ -671,8 +1489,7 @@ fn test_bot_row_puzzle_access() {
 
 #[test]
 fn test_top_row_solution_access() {
-    let mut board: SudokuBoard = SudokuBoard::new();
-    board.puzzle = [
+    let mut board = SudokuBoard::from_puzzle([
         [0, 0, 4, 0, 0, 0, 6, 0, 0],
         [2, 7, 0, 0, 0, 0, 0, 9, 0],
         [0, 0, 0, 2, 8, 0, 0, 0, 0],
@@ -682,12 +1499,13 @@ fn test_top_row_solution_access() {
         [0, 8, 0, 3, 0, 4, 0, 0, 0],
         [9, 5, 0, 0, 0, 0, 0, 8, 0],
         [0, 0, 0, 0, 1, 0, 0, 0, 0],
-    ];
+    ]);
     for i in 0..9 {
         for j in 0..9 {
             if board.puzzle[i][j] == 0 {
                 board.possible_solutions[i][j] = SudokuBoard::get_inverse_values(
-                    SudokuBoard::get_existing_values(&board.puzzle, i, j),
+                    SudokuBoard::get_existing_values(&board, i, j),
+                    9,
                 );
             } else {
                 board.possible_solutions[i][j] = Default::default();
@@ -703,8 +1521,7 @@ fn test_top_row_solution_access() {
 
 #[test]
 fn test_left_col_solution_access() {
-    let mut board: SudokuBoard = SudokuBoard::new();
-    board.puzzle = [
+    let mut board = SudokuBoard::from_puzzle([
         [0, 0, 4, 0, 0, 0, 6, 0, 0],
         [2, 7, 0, 0, 0, 0, 0, 9, 0],
         [0, 0, 0, 2, 8, 0, 0, 0, 0],
@@ -714,12 +1531,13 @@ fn test_left_col_solution_access() {
         [0, 8, 0, 3, 0, 4, 0, 0, 0],
         [9, 5, 0, 0, 0, 0, 0, 8, 0],
         [0, 0, 0, 0, 1, 0, 0, 0, 0],
-    ];
+    ]);
     for i in 0..9 {
         for j in 0..9 {
             if board.puzzle[i][j] == 0 {
                 board.possible_solutions[i][j] = SudokuBoard::get_inverse_values(
-                    SudokuBoard::get_existing_values(&board.puzzle, i, j),
+                    SudokuBoard::get_existing_values(&board, i, j),
+                    9,
                 );
             } else {
                 board.possible_solutions[i][j] = Default::default();
@@ -734,6 +1552,7 @@ fn test_left_col_solution_access() {
 }
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn test_removal_from_cell() {
     let mut board: SudokuBoard = SudokuBoard::from_puzzle([
         [0, 0, 4, 0, 0, 0, 6, 0, 0],
@@ -760,6 +1579,7 @@ fn test_removal_from_cell() {
 }
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 pub fn valid_puzzle() {
     let board: SudokuBoard = SudokuBoard::from_puzzle([
         [4, 3, 6, 8, 1, 7, 9, 2, 5],
@@ -777,6 +1597,7 @@ pub fn valid_puzzle() {
 }
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 pub fn invalid_puzzle() {
     let board: SudokuBoard = SudokuBoard::from_puzzle([
         [4, 3, 6, 8, 1, 7, 9, 2, 5],
@@ -794,6 +1615,20 @@ pub fn invalid_puzzle() {
 }
 
 #[test]
+fn rejects_repeats_within_a_box() {
+    // Every row and column is a shift of 1-9, so only the boxes repeat.
+    let board = SudokuBoard::from_puzzle_string(
+        "123456789234567891345678912456789123567891234678912345789123456891234567912345678",
+    )
+    .unwrap();
+    assert!(SudokuBoard::validate_rows(&board));
+    assert!(SudokuBoard::validate_columns(&board));
+    assert!(!SudokuBoard::validate_boxes(&board));
+    assert!(!SudokuBoard::validate_board(&board));
+}
+
+#[test]
+#[allow(clippy::bool_assert_comparison)]
 pub fn solve_expert() {
     let mut s_board = SudokuBoard::from_puzzle([
         [0, 3, 0, 8, 0, 7, 0, 0, 5],
@@ -821,6 +1656,7 @@ pub fn solve_expert() {
 }
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 pub fn solve_medium() {
     let mut s_board = SudokuBoard::from_puzzle([
         [0, 1, 0, 0, 0, 0, 0, 0, 0],
@@ -848,6 +1684,64 @@ pub fn solve_medium() {
 }
 
 #[test]
+pub fn puzzle_string_round_trip() {
+    let line = ".3.8.7..5.....5..3...6..1..6..4..2..2.....489.8.....3...27..........6....97....42";
+    let board = SudokuBoard::from_puzzle_string(line).unwrap();
+    assert_eq!(board.puzzle[0][1], 3);
+    assert_eq!(board.puzzle[8][8], 2);
+    assert_eq!(SudokuBoard::to_puzzle_string(&board.puzzle), line);
+    assert!(SudokuBoard::from_puzzle_string("123").is_none());
+}
+
+#[test]
+pub fn symbol_strings_round_trip() {
+    let letters = SymbolSet::letters(9);
+    let line = ".C.H.G..E.....E..C...F..A..F..D..B..B.....DHI.H.....C...BG..........F....IG....DB";
+    let board = SudokuBoard::from_symbol_string(line, &letters).unwrap();
+    assert_eq!(board.puzzle[0][1], 3);
+    assert_eq!(SudokuBoard::to_symbol_string(&board.puzzle, &letters), line);
+    assert_eq!(
+        SudokuBoard::to_puzzle_string(&board.puzzle),
+        ".3.8.7..5.....5..3...6..1..6..4..2..2.....489.8.....3...27..........6....97....42"
+    );
+
+    let hex = SymbolSet::hexadecimal();
+    let mut sixteen = SudokuBoard::with_shape(BoxShape::new(4, 4));
+    sixteen.puzzle[0][0] = 1;
+    sixteen.puzzle[15][15] = 16;
+    let line = SudokuBoard::to_symbol_string(&sixteen.puzzle, &hex);
+    assert!(line.starts_with("0.") && line.ends_with(".F"));
+    let parsed = SudokuBoard::from_symbol_string(&line, &hex).unwrap();
+    assert_eq!(parsed.puzzle, sixteen.puzzle);
+    assert_eq!(parsed.box_shape, Some(BoxShape::new(4, 4)));
+    assert!(SudokuBoard::from_symbol_string(&line, &SymbolSet::letters(9)).is_none());
+}
+
+#[test]
+pub fn backtracking_counts_solutions() {
+    let board = SudokuBoard::from_puzzle([
+        [0, 3, 0, 8, 0, 7, 0, 0, 5],
+        [0, 0, 0, 0, 0, 5, 0, 0, 3],
+        [0, 0, 0, 6, 0, 0, 1, 0, 0],
+        [6, 0, 0, 4, 0, 0, 2, 0, 0],
+        [2, 0, 0, 0, 0, 0, 4, 8, 9],
+        [0, 8, 0, 0, 0, 0, 0, 3, 0],
+        [0, 0, 2, 7, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 6, 0, 0, 0],
+        [0, 9, 7, 0, 0, 0, 0, 4, 2],
+    ]);
+    assert_eq!(SudokuBoard::count_solutions(&board, 2), 1);
+    let solved = SudokuBoard::solve_backtracking(&board).unwrap();
+    assert!(SudokuBoard::validate_board(&solved));
+
+    let mut open = board.clone();
+    open.puzzle[0][1] = 0;
+    open.puzzle[0][3] = 0;
+    assert_eq!(SudokuBoard::count_solutions(&open, 2), 2);
+}
+
+#[test]
+#[allow(clippy::bool_assert_comparison)]
 pub fn solve_easy() {
     let mut s_board = SudokuBoard::from_puzzle([
         [0, 6, 0, 0, 3, 0, 8, 7, 0],
@@ -873,3 +1767,151 @@ pub fn solve_easy() {
         }
     }
 }
+
+#[test]
+pub fn box_shapes_for_sizes() {
+    assert_eq!(BoxShape::for_size(4), Some(BoxShape::new(2, 2)));
+    assert_eq!(BoxShape::for_size(6), Some(BoxShape::new(2, 3)));
+    assert_eq!(BoxShape::for_size(9), Some(BoxShape::CLASSIC));
+    assert_eq!(BoxShape::for_size(12), Some(BoxShape::new(3, 4)));
+    assert_eq!(BoxShape::for_size(25), Some(BoxShape::new(5, 5)));
+    assert_eq!(BoxShape::for_size(7), None);
+    assert_eq!(BoxShape::for_size(36), None);
+    assert!(SudokuBoard::from_puzzle_string(&".".repeat(36 * 36)).is_none());
+}
+
+#[test]
+#[should_panic]
+pub fn rejects_boards_above_max_size() {
+    SudokuBoard::with_shape(BoxShape::new(6, 6));
+}
+
+#[test]
+pub fn rejects_grids_that_do_not_fit() {
+    assert_eq!(
+        SudokuBoard::try_with_shape(BoxShape::new(6, 6)).err(),
+        Some(BoardError::BadSize { size: 36 })
+    );
+    assert_eq!(
+        SudokuBoard::try_with_regions(Regions::boxes(BoxShape::new(2, 13))).err(),
+        Some(BoardError::BadSize { size: 26 })
+    );
+    assert_eq!(
+        SudokuBoard::try_from_grid(vec![vec![0; 4]; 3], BoxShape::new(2, 2)).err(),
+        Some(BoardError::WrongDimensions { size: 4 })
+    );
+    let mut ragged = vec![vec![0; 4]; 4];
+    ragged[2].pop();
+    assert_eq!(
+        SudokuBoard::try_from_jigsaw(ragged, Regions::boxes(BoxShape::new(2, 2))).err(),
+        Some(BoardError::WrongDimensions { size: 4 })
+    );
+    let mut too_big = vec![vec![0; 4]; 4];
+    too_big[1][3] = 5;
+    assert_eq!(
+        SudokuBoard::try_from_grid(too_big, BoxShape::new(2, 2)).err(),
+        Some(BoardError::BadValue {
+            row: 1,
+            col: 3,
+            value: 5
+        })
+    );
+    let board = SudokuBoard::try_from_grid(vec![vec![0; 4]; 4], BoxShape::new(2, 2)).unwrap();
+    assert_eq!(board.possible_solutions[0][0], vec![1, 2, 3, 4]);
+}
+
+#[test]
+pub fn solves_small_boards() {
+    let mut four =
+        SudokuBoard::from_puzzle([[1, 0, 0, 0], [0, 0, 3, 0], [0, 4, 0, 0], [0, 0, 0, 2]]);
+    SudokuBoard::solve_deterministic(&mut four);
+    assert!(SudokuBoard::validate_board(&four));
+
+    let six = SudokuBoard::from_puzzle_string("5...6. .43.2. ...... ...5.3 3....2 ..2...").unwrap();
+    assert_eq!(six.box_shape, Some(BoxShape::new(2, 3)));
+    assert_eq!(SudokuBoard::count_solutions(&six, 2), 1);
+    let solved = SudokuBoard::solve_backtracking(&six).unwrap();
+    assert!(SudokuBoard::validate_board(&solved));
+
+    let mut broken = solved.clone();
+    broken.puzzle[0].swap(0, 3);
+    assert!(!SudokuBoard::validate_board(&broken));
+}
+
+#[test]
+pub fn solves_large_boards() {
+    for shape in [
+        BoxShape::new(3, 4),
+        BoxShape::new(4, 4),
+        BoxShape::new(5, 5),
+    ] {
+        // A valid grid by shifting each row, with a third of it cleared.
+        let size = shape.size();
+        let mut board = SudokuBoard::with_shape(shape);
+        for i in 0..size {
+            for j in 0..size {
+                if (i + j) % 3 != 0 {
+                    board.puzzle[i][j] =
+                        (((i % shape.rows) * shape.cols + i / shape.rows + j) % size + 1) as i32;
+                }
+            }
+        }
+        let solved = SudokuBoard::solve_backtracking(&board).unwrap();
+        assert!(SudokuBoard::validate_board(&solved));
+    }
+}
+
+#[test]
+pub fn solves_jigsaw() {
+    // The classic boxes with r1c3 and r3c4 traded, and r4c7 and r6c6.
+    let regions = Regions::parse(
+        "112222333 111222333 111122333 444555566 444555666 444556666 777888999 777888999 777888999",
+    )
+    .unwrap();
+    let solution = vec![
+        vec![4, 3, 6, 8, 1, 7, 9, 2, 5],
+        vec![7, 1, 9, 2, 4, 5, 8, 6, 3],
+        vec![8, 2, 5, 6, 3, 9, 1, 7, 4],
+        vec![6, 5, 3, 4, 9, 8, 2, 1, 7],
+        vec![2, 7, 1, 5, 6, 3, 4, 8, 9],
+        vec![9, 8, 4, 1, 7, 2, 5, 3, 6],
+        vec![1, 6, 2, 7, 5, 4, 3, 9, 8],
+        vec![3, 4, 8, 9, 2, 6, 7, 5, 1],
+        vec![5, 9, 7, 3, 8, 1, 6, 4, 2],
+    ];
+    assert!(SudokuBoard::validate_board(&SudokuBoard::from_jigsaw(
+        solution.clone(),
+        regions.clone()
+    )));
+    let mut swapped = solution.clone();
+    swapped.swap(0, 1);
+    assert!(SudokuBoard::validate_board(
+        &SudokuBoard::from_puzzle_string(&SudokuBoard::to_puzzle_string(&swapped)).unwrap()
+    ));
+    assert!(!SudokuBoard::validate_board(&SudokuBoard::from_jigsaw(
+        swapped,
+        regions.clone()
+    )));
+
+    let mut puzzle = solution.clone();
+    for (i, row) in puzzle.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            if (i * 9 + j) % 3 == 0 {
+                *value = 0;
+            }
+        }
+    }
+    let mut board = SudokuBoard::from_jigsaw(puzzle, regions);
+    assert_eq!(board.box_shape, None);
+    assert_eq!(board.size(), 9);
+    assert_eq!(SudokuBoard::get_boundaries_for_cell(&board, 2, 3), (0, 0));
+    assert_eq!(SudokuBoard::get_boundaries_for_cell(&board, 1, 4), (0, 2));
+    for (i, j) in board.regions.cells(0) {
+        for (r, c) in board.regions.cells(0) {
+            assert!(!board.possible_solutions[*i][*j].contains(&board.puzzle[*r][*c]));
+        }
+    }
+    assert_eq!(SudokuBoard::count_solutions(&board, 2), 1);
+    SudokuBoard::solve_deterministic(&mut board);
+    assert_eq!(board.puzzle, solution);
+}
@@ -0,0 +1,132 @@
+use std::fmt;
+
+/// The characters used to write cell values, e.g. `1`–`9`, `A`–`I` or hex
+/// digits. Value `v` is written as the `v`th symbol; empty cells are `.`.
+/// Only parsing and display go through a symbol set; boards always hold the
+/// values 1 to `size`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolSet {
+    symbols: Vec<char>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SymbolError {
+    Empty,
+    Duplicate(char),
+    /// `.` and whitespace are reserved for empty cells and layout.
+    Reserved(char),
+}
+
+impl fmt::Display for SymbolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SymbolError::Empty => write!(f, "symbol set has no symbols"),
+            SymbolError::Duplicate(symbol) => write!(f, "symbol '{}' is used twice", symbol),
+            SymbolError::Reserved(symbol) => {
+                write!(f, "symbol '{}' is reserved", symbol.escape_default())
+            }
+        }
+    }
+}
+
+impl std::error::Error for SymbolError {}
+
+const DEFAULT_SYMBOLS: &str = "123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+impl SymbolSet {
+    /// Symbols in value order: the first character stands for 1.
+    pub fn new(symbols: &str) -> Result<SymbolSet, SymbolError> {
+        let symbols: Vec<char> = symbols.chars().collect();
+        if symbols.is_empty() {
+            return Err(SymbolError::Empty);
+        }
+        for (index, symbol) in symbols.iter().enumerate() {
+            if *symbol == '.' || symbol.is_whitespace() {
+                return Err(SymbolError::Reserved(*symbol));
+            }
+            if symbols[..index].contains(symbol) {
+                return Err(SymbolError::Duplicate(*symbol));
+            }
+        }
+        Ok(SymbolSet { symbols })
+    }
+
+    /// Digits from 1, then letters from A: `1`–`9` for a classic board and
+    /// `1`–`9`, `A`–`G` for 16x16. Supports sides up to 35.
+    pub fn for_size(size: usize) -> SymbolSet {
+        SymbolSet {
+            symbols: DEFAULT_SYMBOLS.chars().take(size).collect(),
+        }
+    }
+
+    /// `0`–`9` then `A`–`F`, the usual way of writing 16x16 puzzles.
+    pub fn hexadecimal() -> SymbolSet {
+        SymbolSet {
+            symbols: "0123456789ABCDEF".chars().collect(),
+        }
+    }
+
+    /// The first `size` capital letters, e.g. `A`–`I` for a classic board.
+    pub fn letters(size: usize) -> SymbolSet {
+        SymbolSet {
+            symbols: ('A'..='Z').take(size).collect(),
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.symbols.len()
+    }
+
+    /// The character for `value`, `.` for an empty cell and `?` for a value
+    /// outside the set.
+    pub fn symbol(&self, value: i32) -> char {
+        if value == 0 {
+            return '.';
+        }
+        usize::try_from(value - 1)
+            .ok()
+            .and_then(|index| self.symbols.get(index))
+            .copied()
+            .unwrap_or('?')
+    }
+
+    /// The value written as `symbol`: 0 for `.`, or for `0` when `0` is not a
+    /// symbol. Letters match in either case when that is unambiguous.
+    pub fn value(&self, symbol: char) -> Option<i32> {
+        if let Some(index) = self.symbols.iter().position(|s| *s == symbol) {
+            return Some(index as i32 + 1);
+        }
+        if symbol == '.' || symbol == '0' {
+            return Some(0);
+        }
+        let mut matches = self
+            .symbols
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.eq_ignore_ascii_case(&symbol));
+        match (matches.next(), matches.next()) {
+            (Some((index, _)), None) => Some(index as i32 + 1),
+            _ => None,
+        }
+    }
+}
+
+#[test]
+fn maps_values_and_symbols() {
+    let letters = SymbolSet::letters(9);
+    assert_eq!(letters.symbol(1), 'A');
+    assert_eq!(letters.symbol(9), 'I');
+    assert_eq!(letters.symbol(0), '.');
+    assert_eq!(letters.symbol(10), '?');
+    assert_eq!(letters.value('c'), Some(3));
+    assert_eq!(letters.value('0'), Some(0));
+    assert_eq!(letters.value('J'), None);
+
+    let hex = SymbolSet::hexadecimal();
+    assert_eq!(hex.value('0'), Some(1));
+    assert_eq!(hex.value('f'), Some(16));
+    assert_eq!(SymbolSet::for_size(16).symbol(16), 'G');
+
+    assert_eq!(SymbolSet::new("AB.C"), Err(SymbolError::Reserved('.')));
+    assert_eq!(SymbolSet::new("ABCA"), Err(SymbolError::Duplicate('A')));
+}
@@ -0,0 +1,145 @@
+use crate::sudoku::Grid;
+use std::fmt;
+
+/// Symmetries of the clue pattern, as used for published puzzles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Symmetry {
+    None,
+    Rotational180,
+    Rotational90,
+    Horizontal,
+    Vertical,
+    Diagonal,
+    AntiDiagonal,
+}
+
+impl Symmetry {
+    pub const ALL: [Symmetry; 7] = [
+        Symmetry::None,
+        Symmetry::Rotational180,
+        Symmetry::Rotational90,
+        Symmetry::Horizontal,
+        Symmetry::Vertical,
+        Symmetry::Diagonal,
+        Symmetry::AntiDiagonal,
+    ];
+
+    /// The map generating the symmetry on a grid of side `size`: a half turn,
+    /// a quarter turn clockwise, or a reflection in the middle row, the middle
+    /// column or a diagonal.
+    pub fn map(&self, cell: (usize, usize), size: usize) -> (usize, usize) {
+        let (row, col) = cell;
+        let last = size - 1;
+        match self {
+            Symmetry::None => (row, col),
+            Symmetry::Rotational180 => (last - row, last - col),
+            Symmetry::Rotational90 => (col, last - row),
+            Symmetry::Horizontal => (last - row, col),
+            Symmetry::Vertical => (row, last - col),
+            Symmetry::Diagonal => (col, row),
+            Symmetry::AntiDiagonal => (last - col, last - row),
+        }
+    }
+
+    /// Every cell the symmetry carries `cell` to, including `cell` itself.
+    pub fn orbit(&self, cell: (usize, usize), size: usize) -> Vec<(usize, usize)> {
+        let mut orbit = vec![cell];
+        let mut next = self.map(cell, size);
+        while next != cell {
+            orbit.push(next);
+            next = self.map(next, size);
+        }
+        orbit
+    }
+
+    /// Partitions the grid into orbits, in row-major order of their first cell.
+    pub fn orbits(&self, size: usize) -> Vec<Vec<(usize, usize)>> {
+        let mut seen = vec![vec![false; size]; size];
+        let mut orbits = Vec::new();
+        for i in 0..size {
+            for j in 0..size {
+                if seen[i][j] {
+                    continue;
+                }
+                let orbit = self.orbit((i, j), size);
+                for (row, col) in &orbit {
+                    seen[*row][*col] = true;
+                }
+                orbits.push(orbit);
+            }
+        }
+        orbits
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Symmetry::None => "none",
+            Symmetry::Rotational180 => "180° rotational",
+            Symmetry::Rotational90 => "90° rotational",
+            Symmetry::Horizontal => "horizontal mirror",
+            Symmetry::Vertical => "vertical mirror",
+            Symmetry::Diagonal => "diagonal mirror",
+            Symmetry::AntiDiagonal => "anti-diagonal mirror",
+        }
+    }
+}
+
+impl fmt::Display for Symmetry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Reports the symmetries of the clue positions in `puzzle`. `Symmetry::None`
+/// is not reported since every pattern trivially has it.
+pub fn detect_symmetries(puzzle: &Grid) -> Vec<Symmetry> {
+    let size = puzzle.len();
+    Symmetry::ALL
+        .iter()
+        .filter(|symmetry| **symmetry != Symmetry::None)
+        .filter(|symmetry| {
+            (0..size * size).all(|i| {
+                let (row, col) = (i / size, i % size);
+                let (m_row, m_col) = symmetry.map((row, col), size);
+                (puzzle[row][col] == 0) == (puzzle[m_row][m_col] == 0)
+            })
+        })
+        .copied()
+        .collect()
+}
+
+#[test]
+fn orbits_partition_the_grid() {
+    for symmetry in Symmetry::ALL {
+        let cells: usize = symmetry.orbits(9).iter().map(|orbit| orbit.len()).sum();
+        assert_eq!(cells, 81);
+    }
+    assert_eq!(Symmetry::Rotational90.orbit((0, 0), 9).len(), 4);
+    assert_eq!(Symmetry::Rotational180.orbit((4, 4), 9).len(), 1);
+    assert_eq!(
+        Symmetry::Rotational180.orbit((1, 1), 4),
+        vec![(1, 1), (2, 2)]
+    );
+}
+
+#[test]
+fn detects_rotational_symmetry() {
+    let puzzle = vec![
+        vec![0, 3, 0, 8, 0, 7, 0, 0, 5],
+        vec![0, 0, 0, 0, 0, 5, 0, 0, 3],
+        vec![0, 0, 0, 6, 0, 0, 1, 0, 0],
+        vec![6, 0, 0, 4, 0, 0, 2, 0, 0],
+        vec![2, 0, 0, 0, 0, 0, 4, 8, 9],
+        vec![0, 8, 0, 0, 0, 0, 0, 3, 0],
+        vec![0, 0, 2, 7, 0, 0, 0, 0, 0],
+        vec![0, 0, 0, 0, 0, 6, 0, 0, 0],
+        vec![0, 9, 7, 0, 0, 0, 0, 4, 2],
+    ];
+    assert_eq!(detect_symmetries(&puzzle), vec![]);
+
+    let mut symmetric = vec![vec![0; 9]; 9];
+    symmetric[0][1] = 4;
+    symmetric[8][7] = 6;
+    symmetric[4][4] = 1;
+    assert_eq!(detect_symmetries(&symmetric), vec![Symmetry::Rotational180]);
+}
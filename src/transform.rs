@@ -0,0 +1,211 @@
+use crate::rng::Rng;
+use crate::sudoku::{BoxShape, Grid};
+
+/// Quarter turn clockwise.
+pub fn rotate(puzzle: &Grid) -> Grid {
+    let size = puzzle.len();
+    let mut ret_value = vec![vec![0; size]; size];
+    for i in 0..size {
+        for j in 0..size {
+            ret_value[j][size - 1 - i] = puzzle[i][j];
+        }
+    }
+    ret_value
+}
+
+pub fn transpose(puzzle: &Grid) -> Grid {
+    let size = puzzle.len();
+    let mut ret_value = vec![vec![0; size]; size];
+    for i in 0..size {
+        for j in 0..size {
+            ret_value[j][i] = puzzle[i][j];
+        }
+    }
+    ret_value
+}
+
+/// Flips top to bottom.
+pub fn mirror_horizontal(puzzle: &Grid) -> Grid {
+    let mut ret_value = puzzle.clone();
+    ret_value.reverse();
+    ret_value
+}
+
+/// Flips left to right.
+pub fn mirror_vertical(puzzle: &Grid) -> Grid {
+    let mut ret_value = puzzle.clone();
+    for row in ret_value.iter_mut() {
+        row.reverse();
+    }
+    ret_value
+}
+
+/// Band `i` of the result is band `order[i]` of `puzzle`. There is one band
+/// per entry of `order`, so the band height is the side over its length.
+pub fn permute_bands(puzzle: &Grid, order: &[usize]) -> Grid {
+    let height = puzzle.len() / order.len();
+    let mut ret_value = puzzle.clone();
+    for band in 0..order.len() {
+        for k in 0..height {
+            ret_value[band * height + k] = puzzle[order[band] * height + k].clone();
+        }
+    }
+    ret_value
+}
+
+pub fn permute_stacks(puzzle: &Grid, order: &[usize]) -> Grid {
+    transpose(&permute_bands(&transpose(puzzle), order))
+}
+
+/// Row `i` of `band` in the result is row `order[i]` of that band in
+/// `puzzle`; bands are `order.len()` rows high.
+pub fn permute_rows_in_band(puzzle: &Grid, band: usize, order: &[usize]) -> Grid {
+    let height = order.len();
+    let mut ret_value = puzzle.clone();
+    for k in 0..height {
+        ret_value[band * height + k] = puzzle[band * height + order[k]].clone();
+    }
+    ret_value
+}
+
+pub fn permute_cols_in_stack(puzzle: &Grid, stack: usize, order: &[usize]) -> Grid {
+    transpose(&permute_rows_in_band(&transpose(puzzle), stack, order))
+}
+
+/// Replaces each digit `d` with `digits[d - 1]`; empty cells stay empty.
+pub fn relabel(puzzle: &Grid, digits: &[i32]) -> Grid {
+    let mut ret_value = puzzle.clone();
+    for row in ret_value.iter_mut() {
+        for cell in row.iter_mut() {
+            if *cell != 0 {
+                *cell = digits[(*cell - 1) as usize];
+            }
+        }
+    }
+    ret_value
+}
+
+/// A combination of the validity-preserving operations: an optional transpose
+/// followed by band, row, stack and column permutations and a relabelling.
+/// Rotations and mirrors are all expressible this way. Transposing turns
+/// rectangular boxes on their side, so it is only used with square boxes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transformation {
+    pub transpose: bool,
+    pub bands: Vec<usize>,
+    pub rows: Vec<Vec<usize>>,
+    pub stacks: Vec<usize>,
+    pub cols: Vec<Vec<usize>>,
+    pub digits: Vec<i32>,
+}
+
+impl Transformation {
+    pub fn identity(box_shape: BoxShape) -> Transformation {
+        let size = box_shape.size();
+        let bands = size / box_shape.rows;
+        let stacks = size / box_shape.cols;
+        Transformation {
+            transpose: false,
+            bands: (0..bands).collect(),
+            rows: vec![(0..box_shape.rows).collect(); bands],
+            stacks: (0..stacks).collect(),
+            cols: vec![(0..box_shape.cols).collect(); stacks],
+            digits: (1..=size as i32).collect(),
+        }
+    }
+
+    pub fn random(box_shape: BoxShape, rng: &mut Rng) -> Transformation {
+        let mut transformation = Transformation::identity(box_shape);
+        transformation.transpose = box_shape.rows == box_shape.cols && rng.below(2) == 1;
+        rng.shuffle(&mut transformation.bands);
+        rng.shuffle(&mut transformation.stacks);
+        for k in 0..transformation.rows.len().max(transformation.cols.len()) {
+            if let Some(rows) = transformation.rows.get_mut(k) {
+                rng.shuffle(rows);
+            }
+            if let Some(cols) = transformation.cols.get_mut(k) {
+                rng.shuffle(cols);
+            }
+        }
+        rng.shuffle(&mut transformation.digits);
+        transformation
+    }
+
+    pub fn apply(&self, puzzle: &Grid) -> Grid {
+        let mut ret_value = if self.transpose {
+            transpose(puzzle)
+        } else {
+            puzzle.clone()
+        };
+        ret_value = permute_bands(&ret_value, &self.bands);
+        ret_value = permute_stacks(&ret_value, &self.stacks);
+        for (k, order) in self.rows.iter().enumerate() {
+            ret_value = permute_rows_in_band(&ret_value, k, order);
+        }
+        for (k, order) in self.cols.iter().enumerate() {
+            ret_value = permute_cols_in_stack(&ret_value, k, order);
+        }
+        relabel(&ret_value, &self.digits)
+    }
+}
+
+#[test]
+fn rotations_compose() {
+    let mut puzzle = vec![vec![0; 9]; 9];
+    puzzle[0][1] = 5;
+    puzzle[3][7] = 2;
+    let turned = rotate(&rotate(&rotate(&rotate(&puzzle))));
+    assert_eq!(turned, puzzle);
+    assert_eq!(rotate(&puzzle)[1][8], 5);
+    assert_eq!(
+        rotate(&rotate(&puzzle)),
+        mirror_horizontal(&mirror_vertical(&puzzle))
+    );
+    assert_eq!(permute_bands(&puzzle, &[1, 0, 2])[0][7], 2);
+    assert_eq!(permute_cols_in_stack(&puzzle, 0, &[1, 0, 2])[0][0], 5);
+}
+
+#[test]
+fn solution_is_invariant_under_transformation() {
+    use crate::sudoku::SudokuBoard;
+
+    let board = SudokuBoard::from_puzzle([
+        [0, 3, 0, 8, 0, 7, 0, 0, 5],
+        [0, 0, 0, 0, 0, 5, 0, 0, 3],
+        [0, 0, 0, 6, 0, 0, 1, 0, 0],
+        [6, 0, 0, 4, 0, 0, 2, 0, 0],
+        [2, 0, 0, 0, 0, 0, 4, 8, 9],
+        [0, 8, 0, 0, 0, 0, 0, 3, 0],
+        [0, 0, 2, 7, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 6, 0, 0, 0],
+        [0, 9, 7, 0, 0, 0, 0, 4, 2],
+    ]);
+    let solution = SudokuBoard::find_solutions(&board, 1).remove(0);
+    let mut rng = Rng::new(35);
+    for _ in 0..10 {
+        let transformation = Transformation::random(board.box_shape.unwrap(), &mut rng);
+        let variant = SudokuBoard::from_grid(
+            transformation.apply(&board.puzzle),
+            board.box_shape.unwrap(),
+        );
+        let solutions = SudokuBoard::find_solutions(&variant, 2);
+        assert_eq!(solutions, vec![transformation.apply(&solution)]);
+        let variant_board = SudokuBoard::from_grid(solutions[0].clone(), board.box_shape.unwrap());
+        assert!(SudokuBoard::validate_board(&variant_board));
+    }
+}
+
+#[test]
+fn transforms_rectangular_boxes() {
+    use crate::sudoku::SudokuBoard;
+
+    let shape = BoxShape::new(2, 3);
+    let solution = SudokuBoard::solve_backtracking(&SudokuBoard::with_shape(shape)).unwrap();
+    let mut rng = Rng::new(37);
+    for _ in 0..10 {
+        let variant = Transformation::random(shape, &mut rng).apply(&solution.puzzle);
+        assert!(SudokuBoard::validate_board(&SudokuBoard::from_grid(
+            variant, shape
+        )));
+    }
+}
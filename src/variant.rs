@@ -0,0 +1,655 @@
+use crate::cages::{Cage, CageError};
+use crate::explain::parse_cell_name;
+use crate::json::{Json, JsonError};
+use crate::lines::{Line, LineError, LineKind};
+use crate::outside::{ClueError, ClueKind, OutsideClue, Side};
+use crate::pairs::{PairConstraint, PairError, PairRelation};
+use crate::parity::{Parity, ParityCell, ParityError};
+use crate::peers::PeerRule;
+use crate::regions::{ExtraKind, ExtraUnit, RegionError, Regions};
+use crate::sudoku::{BoxShape, SudokuBoard, MAX_SIZE};
+use crate::symbols::SymbolSet;
+use std::fmt;
+
+/// Why a variant description, or an f-puzzles export read by
+/// `fpuzzles::import_fpuzzles`, could not be turned into a board.
+#[derive(Debug, Clone, PartialEq)]
+pub enum VariantError {
+    Json(JsonError),
+    /// An export that is neither JSON nor LZString-compressed base64.
+    NotCompressed,
+    /// A key the description format does not use, often a typo.
+    UnknownKey {
+        key: String,
+    },
+    WrongType {
+        key: String,
+        expected: &'static str,
+    },
+    /// A name such as a line type or rule that is not recognised.
+    UnknownName {
+        key: String,
+        name: String,
+    },
+    BadCell {
+        name: String,
+    },
+    /// A constraint the solver has no equivalent for, named as in the
+    /// export.
+    Unsupported {
+        constraint: String,
+    },
+    /// The givens are not one symbol or `.` per cell.
+    BadGivens,
+    /// A board, box shape or region layout with no cells or larger than
+    /// `MAX_SIZE`.
+    BadSize {
+        size: usize,
+    },
+    /// The size has no standard box shape and neither `box` nor `regions`
+    /// is given.
+    NoBoxShape {
+        size: usize,
+    },
+    /// Extra units such as windows that are laid out by box, asked of a
+    /// board with irregular regions.
+    NeedsBoxes {
+        name: String,
+    },
+    Regions(RegionError),
+    Cages(CageError),
+    Pairs(PairError),
+    Lines(LineError),
+    Clues(ClueError),
+    Parity(ParityError),
+}
+
+impl fmt::Display for VariantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VariantError::Json(error) => write!(f, "{}", error),
+            VariantError::NotCompressed => write!(f, "not an f-puzzles export"),
+            VariantError::UnknownKey { key } => write!(f, "unknown key '{}'", key),
+            VariantError::WrongType { key, expected } => {
+                write!(f, "'{}' should be {}", key, expected)
+            }
+            VariantError::UnknownName { key, name } => {
+                write!(f, "'{}' is not a known {}", name, key)
+            }
+            VariantError::BadCell { name } => write!(f, "'{}' is not a cell like r1c1", name),
+            VariantError::Unsupported { constraint } => {
+                write!(f, "{} is not supported", constraint)
+            }
+            VariantError::BadGivens => write!(f, "givens do not fill the board"),
+            VariantError::BadSize { size } => write!(
+                f,
+                "a board of size {} is not between 1 and {}",
+                size, MAX_SIZE
+            ),
+            VariantError::NoBoxShape { size } => {
+                write!(f, "a {}x{} board needs a box shape or regions", size, size)
+            }
+            VariantError::NeedsBoxes { name } => {
+                write!(f, "{} need a board with rectangular boxes", name)
+            }
+            VariantError::Regions(error) => write!(f, "{}", error),
+            VariantError::Cages(error) => write!(f, "{}", error),
+            VariantError::Pairs(error) => write!(f, "{}", error),
+            VariantError::Lines(error) => write!(f, "{}", error),
+            VariantError::Clues(error) => write!(f, "{}", error),
+            VariantError::Parity(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for VariantError {}
+
+impl From<JsonError> for VariantError {
+    fn from(error: JsonError) -> VariantError {
+        VariantError::Json(error)
+    }
+}
+
+impl From<RegionError> for VariantError {
+    fn from(error: RegionError) -> VariantError {
+        VariantError::Regions(error)
+    }
+}
+
+impl From<CageError> for VariantError {
+    fn from(error: CageError) -> VariantError {
+        VariantError::Cages(error)
+    }
+}
+
+impl From<PairError> for VariantError {
+    fn from(error: PairError) -> VariantError {
+        VariantError::Pairs(error)
+    }
+}
+
+impl From<LineError> for VariantError {
+    fn from(error: LineError) -> VariantError {
+        VariantError::Lines(error)
+    }
+}
+
+impl From<ClueError> for VariantError {
+    fn from(error: ClueError) -> VariantError {
+        VariantError::Clues(error)
+    }
+}
+
+impl From<ParityError> for VariantError {
+    fn from(error: ParityError) -> VariantError {
+        VariantError::Parity(error)
+    }
+}
+
+const KEYS: [&str; 14] = [
+    "size",
+    "box",
+    "regions",
+    "givens",
+    "extra_units",
+    "rules",
+    "cages",
+    "pairs",
+    "negative",
+    "lines",
+    "clues",
+    "even",
+    "odd",
+    "title",
+];
+
+/// Builds a board from a JSON description of its rules. Every key is
+/// optional; cells are written like "r1c1".
+///
+/// ```json
+/// {
+///   "title": "Example",
+///   "size": 9,
+///   "box": [3, 3],
+///   "regions": "aaabbbccc ...",
+///   "givens": "1....7.9. ...",
+///   "extra_units": ["diagonals", "windows", ["r1c1", "r2c3", "r3c5"]],
+///   "rules": ["anti-knight", "anti-king", "non-consecutive"],
+///   "cages": [{"cells": ["r1c1", "r1c2"], "sum": 3}],
+///   "pairs": [{"type": "white", "cells": ["r1c1", "r2c1"]}],
+///   "negative": ["kropki", "xv"],
+///   "lines": [{"type": "thermo", "cells": ["r5c2", "r4c2", "r4c3"]}],
+///   "clues": [{"type": "little killer", "side": "top", "index": 2,
+///              "direction": "right", "value": 35}],
+///   "even": ["r1c2"],
+///   "odd": ["r1c1"]
+/// }
+/// ```
+///
+/// `regions` is a jigsaw layout as for `Regions::parse`. Extra units are
+/// "diagonals", "windows", "disjoint groups", "argyle", "girandola" or a
+/// list of cells. Pairs are "white", "black", "x" or "v", and `negative`
+/// adds the Kropki or XV negative constraint on every unmarked neighbouring
+/// pair. Lines are "thermo", "arrow", "renban", "whisper" or "palindrome",
+/// from the bulb or circle. Clues are "sandwich", "skyscraper" or "little
+/// killer" on a side, next to the one-based row or column `index`; a little
+/// killer's `direction` is the side its diagonal heads towards.
+pub fn load_variant(text: &str) -> Result<SudokuBoard, VariantError> {
+    let json = Json::parse(text)?;
+    let entries = object(&json, "description")?;
+    check_keys(entries, &KEYS)?;
+
+    let givens = json
+        .get("givens")
+        .map(|g| string(g, "givens"))
+        .transpose()?;
+    let regions = match json.get("regions") {
+        Some(layout) => Some(Regions::parse(string(layout, "regions")?)?),
+        None => None,
+    };
+    let shape = match json
+        .get("box")
+        .map(|shape| array(shape, "box"))
+        .transpose()?
+    {
+        Some([rows, cols]) => Some(BoxShape::new(
+            number(rows, "box")? as usize,
+            number(cols, "box")? as usize,
+        )),
+        Some(_) => return Err(wrong_type("box", "[rows, columns]")),
+        None => None,
+    };
+    let size = match (json.get("size"), &regions, shape, givens) {
+        (Some(size), _, _, _) => number(size, "size")? as usize,
+        (None, Some(regions), _, _) => regions.size(),
+        (None, None, Some(shape), _) => shape.size(),
+        (None, None, None, Some(givens)) => {
+            let cells = givens.chars().filter(|c| !c.is_whitespace()).count();
+            (1..=cells)
+                .find(|size| size * size == cells)
+                .ok_or(VariantError::BadGivens)?
+        }
+        (None, None, None, None) => 9,
+    };
+    let sizes = [
+        Some(size),
+        regions.as_ref().map(Regions::size),
+        shape.map(|shape| shape.rows.saturating_mul(shape.cols)),
+    ];
+    if let Some(size) = sizes
+        .into_iter()
+        .flatten()
+        .find(|size| !(1..=MAX_SIZE).contains(size))
+    {
+        return Err(VariantError::BadSize { size });
+    }
+    let mut board = match (regions, shape) {
+        (Some(regions), _) => SudokuBoard::with_regions(regions),
+        (None, Some(shape)) => SudokuBoard::with_shape(shape),
+        (None, None) => SudokuBoard::with_shape(
+            BoxShape::for_size(size).ok_or(VariantError::NoBoxShape { size })?,
+        ),
+    };
+    if board.size() != size {
+        return Err(wrong_type("size", "the size of the boxes or regions"));
+    }
+    if let Some(givens) = givens {
+        board.puzzle = read_givens(givens, size)?;
+    }
+
+    if let Some(units) = json.get("extra_units") {
+        let mut extra_units = Vec::new();
+        let mut custom = 0;
+        for unit in array(units, "extra_units")? {
+            match unit {
+                Json::Array(_) => {
+                    extra_units.push(ExtraUnit {
+                        kind: ExtraKind::Custom(custom),
+                        cells: cells(unit, "extra_units")?,
+                    });
+                    custom += 1;
+                }
+                _ => extra_units.extend(match string(unit, "extra_units")? {
+                    "diagonals" => ExtraUnit::diagonals(size),
+                    "windows" => ExtraUnit::windows(box_shape(&board, "windows")?),
+                    "disjoint groups" => {
+                        ExtraUnit::disjoint_groups(box_shape(&board, "disjoint groups")?)
+                    }
+                    "argyle" => ExtraUnit::argyle(),
+                    "girandola" => vec![ExtraUnit::girandola()],
+                    name => return Err(unknown("extra unit", name)),
+                }),
+            }
+        }
+        SudokuBoard::add_extra_units(&mut board, extra_units)?;
+    }
+
+    for rule in json
+        .get("rules")
+        .map_or(Ok(&[][..]), |r| array(r, "rules"))?
+    {
+        match string(rule, "rules")? {
+            "anti-knight" => SudokuBoard::add_peer_rule(&mut board, PeerRule::AntiKnight),
+            "anti-king" => SudokuBoard::add_peer_rule(&mut board, PeerRule::AntiKing),
+            "non-consecutive" => SudokuBoard::add_pair_constraints(
+                &mut board,
+                PairConstraint::non_consecutive(size),
+            )?,
+            name => return Err(unknown("rule", name)),
+        }
+    }
+
+    if let Some(cages) = json.get("cages") {
+        let cages = array(cages, "cages")?
+            .iter()
+            .map(|cage| {
+                check_keys(object(cage, "cages")?, &["cells", "sum"])?;
+                let sum = required(cage, "sum").and_then(|sum| number(sum, "sum"))?;
+                Ok(Cage::new(
+                    cells(required(cage, "cells")?, "cells")?,
+                    sum as i32,
+                ))
+            })
+            .collect::<Result<Vec<Cage>, VariantError>>()?;
+        SudokuBoard::add_cages(&mut board, cages)?;
+    }
+
+    if let Some(pairs) = json.get("pairs") {
+        let mut marks = Vec::new();
+        for pair in array(pairs, "pairs")? {
+            check_keys(object(pair, "pairs")?, &["type", "cells"])?;
+            let relation = match string(required(pair, "type")?, "type")? {
+                "white" => PairRelation::Consecutive,
+                "black" => PairRelation::Ratio,
+                "x" => PairRelation::X,
+                "v" => PairRelation::V,
+                name => return Err(unknown("pair", name)),
+            };
+            match cells(required(pair, "cells")?, "cells")?[..] {
+                [a, b] => marks.push(PairConstraint::new(a, b, relation)),
+                _ => {
+                    return Err(VariantError::WrongType {
+                        key: "cells".to_string(),
+                        expected: "two cells",
+                    })
+                }
+            }
+        }
+        SudokuBoard::add_pair_constraints(&mut board, marks)?;
+    }
+    for negative in json
+        .get("negative")
+        .map_or(Ok(&[][..]), |n| array(n, "negative"))?
+    {
+        let marks = |relations: &[PairRelation]| -> Vec<PairConstraint> {
+            board
+                .pair_constraints
+                .iter()
+                .filter(|pair| relations.contains(&pair.relation))
+                .copied()
+                .collect()
+        };
+        let unmarked = match string(negative, "negative")? {
+            "kropki" => PairConstraint::kropki_negative(
+                size,
+                &marks(&[PairRelation::Consecutive, PairRelation::Ratio]),
+            ),
+            "xv" => PairConstraint::xv_negative(size, &marks(&[PairRelation::X, PairRelation::V])),
+            name => return Err(unknown("negative constraint", name)),
+        };
+        SudokuBoard::add_pair_constraints(&mut board, unmarked)?;
+    }
+
+    if let Some(lines) = json.get("lines") {
+        let lines = array(lines, "lines")?
+            .iter()
+            .map(|line| {
+                check_keys(object(line, "lines")?, &["type", "cells"])?;
+                let kind = match string(required(line, "type")?, "type")? {
+                    "thermo" => LineKind::Thermo,
+                    "arrow" => LineKind::Arrow,
+                    "renban" => LineKind::Renban,
+                    "whisper" => LineKind::Whisper,
+                    "palindrome" => LineKind::Palindrome,
+                    name => return Err(unknown("line", name)),
+                };
+                Ok(Line::new(kind, cells(required(line, "cells")?, "cells")?))
+            })
+            .collect::<Result<Vec<Line>, VariantError>>()?;
+        SudokuBoard::add_lines(&mut board, lines)?;
+    }
+
+    if let Some(clues) = json.get("clues") {
+        let clues = array(clues, "clues")?
+            .iter()
+            .map(read_clue)
+            .collect::<Result<Vec<OutsideClue>, VariantError>>()?;
+        SudokuBoard::add_outside_clues(&mut board, clues)?;
+    }
+
+    let mut parity_cells = Vec::new();
+    for (key, parity) in [("even", Parity::Even), ("odd", Parity::Odd)] {
+        if let Some(marked) = json.get(key) {
+            for cell in cells(marked, key)? {
+                parity_cells.push(ParityCell::new(cell, parity));
+            }
+        }
+    }
+    SudokuBoard::add_parity_cells(&mut board, parity_cells)?;
+    Ok(board)
+}
+
+fn read_givens(givens: &str, size: usize) -> Result<Vec<Vec<i32>>, VariantError> {
+    let symbols = SymbolSet::for_size(size);
+    let values = givens
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| match c {
+            '.' | '0' => Some(0),
+            _ => symbols.value(c),
+        })
+        .collect::<Option<Vec<i32>>>()
+        .ok_or(VariantError::BadGivens)?;
+    if values.len() != size * size {
+        return Err(VariantError::BadGivens);
+    }
+    Ok(values.chunks(size).map(|row| row.to_vec()).collect())
+}
+
+fn read_clue(clue: &Json) -> Result<OutsideClue, VariantError> {
+    check_keys(
+        object(clue, "clues")?,
+        &["type", "side", "index", "value", "direction"],
+    )?;
+    let side = |key: &str| -> Result<Side, VariantError> {
+        match string(required(clue, key)?, key)? {
+            "top" => Ok(Side::Top),
+            "bottom" => Ok(Side::Bottom),
+            "left" => Ok(Side::Left),
+            "right" => Ok(Side::Right),
+            name => Err(unknown("side", name)),
+        }
+    };
+    let kind = match string(required(clue, "type")?, "type")? {
+        "sandwich" => ClueKind::Sandwich,
+        "skyscraper" => ClueKind::Skyscraper,
+        "little killer" => ClueKind::LittleKiller(side("direction")?),
+        name => return Err(unknown("clue", name)),
+    };
+    let index = number(required(clue, "index")?, "index")?;
+    if index < 1 {
+        return Err(VariantError::WrongType {
+            key: "index".to_string(),
+            expected: "a row or column from 1",
+        });
+    }
+    let value = number(required(clue, "value")?, "value")?;
+    Ok(OutsideClue::new(
+        kind,
+        side("side")?,
+        index as usize - 1,
+        value as i32,
+    ))
+}
+
+/// The boxes that `name` is laid out by.
+fn box_shape(board: &SudokuBoard, name: &str) -> Result<BoxShape, VariantError> {
+    board.box_shape.ok_or(VariantError::NeedsBoxes {
+        name: name.to_string(),
+    })
+}
+
+fn unknown(key: &str, name: &str) -> VariantError {
+    VariantError::UnknownName {
+        key: key.to_string(),
+        name: name.to_string(),
+    }
+}
+
+fn check_keys(entries: &[(String, Json)], keys: &[&str]) -> Result<(), VariantError> {
+    match entries
+        .iter()
+        .find(|(key, _)| !keys.contains(&key.as_str()))
+    {
+        Some((key, _)) => Err(VariantError::UnknownKey { key: key.clone() }),
+        None => Ok(()),
+    }
+}
+
+pub(crate) fn required<'a>(json: &'a Json, key: &str) -> Result<&'a Json, VariantError> {
+    json.get(key).ok_or(VariantError::WrongType {
+        key: key.to_string(),
+        expected: "given",
+    })
+}
+
+pub(crate) fn unsupported(constraint: &str) -> VariantError {
+    VariantError::Unsupported {
+        constraint: constraint.to_string(),
+    }
+}
+
+pub(crate) fn wrong_type(key: &str, expected: &'static str) -> VariantError {
+    VariantError::WrongType {
+        key: key.to_string(),
+        expected,
+    }
+}
+
+fn object<'a>(json: &'a Json, key: &str) -> Result<&'a [(String, Json)], VariantError> {
+    json.as_object().ok_or(wrong_type(key, "an object"))
+}
+
+pub(crate) fn array<'a>(json: &'a Json, key: &str) -> Result<&'a [Json], VariantError> {
+    json.as_array().ok_or(wrong_type(key, "a list"))
+}
+
+pub(crate) fn string<'a>(json: &'a Json, key: &str) -> Result<&'a str, VariantError> {
+    json.as_str().ok_or(wrong_type(key, "a string"))
+}
+
+fn number(json: &Json, key: &str) -> Result<i64, VariantError> {
+    json.as_i64()
+        .filter(|number| *number >= 0)
+        .ok_or(wrong_type(key, "a whole number"))
+}
+
+pub(crate) fn cell(name: &str) -> Result<(usize, usize), VariantError> {
+    parse_cell_name(name).ok_or(VariantError::BadCell {
+        name: name.to_string(),
+    })
+}
+
+/// A list of cell names such as `["r1c1", "r1c2"]`.
+pub(crate) fn cells(json: &Json, key: &str) -> Result<Vec<(usize, usize)>, VariantError> {
+    array(json, key)?
+        .iter()
+        .map(|name| cell(string(name, key)?))
+        .collect()
+}
+
+#[test]
+fn loads_variant_descriptions() {
+    use crate::logic::{self, Technique, Unit};
+
+    let description = r#"{
+            "title": "Lines",
+            "givens": "..6.........7...4......4.......53.........52.......7.............3.....16...8...3",
+            "lines": [
+                {"type": "thermo", "cells": ["r5c4", "r6c5", "r5c5", "r5c6"]},
+                {"type": "arrow", "cells": ["r8c2", "r9c2", "r9c3"]},
+                {"type": "renban", "cells": ["r5c3", "r4c4", "r3c3"]},
+                {"type": "whisper", "cells": ["r1c7", "r2c7", "r1c6", "r2c6"]},
+                {"type": "palindrome", "cells": ["r2c4", "r3c4", "r4c3", "r4c2"]}
+            ],
+            "clues": [{"type": "sandwich", "side": "left", "index": 2, "value": 16}],
+            "even": ["r1c1"]
+        }"#;
+    let plain = SudokuBoard::from_puzzle_string(
+        "..6.........7...4......4.......53.........52.......7.............3.....16...8...3",
+    )
+    .unwrap();
+    assert_eq!(SudokuBoard::count_solutions(&plain, 2), 2);
+    let board = load_variant(description).unwrap();
+    assert_eq!(board.lines.len(), 5);
+    // The palindrome mirrors the 7 in r2c4 into r4c2.
+    assert_eq!(plain.possible_solutions[3][1], vec![1, 2, 4, 6, 7, 8, 9]);
+    assert_eq!(board.possible_solutions[3][1], vec![7]);
+    assert_eq!(plain.possible_solutions[0][0], vec![1, 2, 3, 4, 5, 7, 8, 9]);
+    assert_eq!(board.possible_solutions[0][0], vec![2, 4, 8]);
+    assert_eq!(board.possible_solutions[1][5], vec![1, 2, 5, 6, 8, 9]);
+
+    let path = logic::solve_path(&board);
+    assert!(path.solved());
+    let step = |technique: Technique, unit: Unit| {
+        path.steps
+            .iter()
+            .find(|step| step.technique == technique && step.units == [unit])
+            .unwrap()
+    };
+    // Among others the whisper rules 5 out of r1c6 and r2c6, as no digit
+    // differs from 5 by five or more.
+    assert_eq!(
+        step(Technique::LineElimination, Unit::Line(3, LineKind::Whisper)).eliminations,
+        vec![(1, 6, 6), (0, 5, 5), (1, 5, 5)]
+    );
+    assert!(!step(
+        Technique::ClueElimination,
+        Unit::Clue(0, ClueKind::Sandwich)
+    )
+    .eliminations
+    .is_empty());
+
+    let board = load_variant(
+        r#"{"size": 6, "rules": ["anti-king"], "extra_units": [["r1c1", "r2c2"]],
+            "cages": [{"cells": ["r1c1", "r1c2"], "sum": 3}],
+            "pairs": [{"type": "x", "cells": ["r6c1", "r6c2"]}], "negative": ["xv"]}"#,
+    )
+    .unwrap();
+    assert_eq!(board.size(), 6);
+    assert_eq!(board.box_shape, Some(BoxShape::new(2, 3)));
+    assert_eq!(board.extra_units[0].kind, ExtraKind::Custom(0));
+    assert_eq!(board.possible_solutions[0][0], vec![1, 2]);
+    assert!(board.pair_constraints.len() > 1);
+
+    assert_eq!(
+        load_variant(r#"{"size": 9, "thermos": []}"#).err(),
+        Some(VariantError::UnknownKey {
+            key: "thermos".to_string()
+        })
+    );
+    assert_eq!(
+        load_variant(r#"{"lines": [{"type": "zipper", "cells": []}]}"#).err(),
+        Some(VariantError::UnknownName {
+            key: "line".to_string(),
+            name: "zipper".to_string()
+        })
+    );
+    assert_eq!(
+        load_variant(r#"{"odd": ["a1"]}"#).err(),
+        Some(VariantError::BadCell {
+            name: "a1".to_string()
+        })
+    );
+    assert_eq!(
+        load_variant(r#"{"size": "nine"}"#).err(),
+        Some(VariantError::WrongType {
+            key: "size".to_string(),
+            expected: "a whole number"
+        })
+    );
+    assert_eq!(
+        load_variant(r#"{"givens": "12"}"#).err(),
+        Some(VariantError::BadGivens)
+    );
+    assert_eq!(
+        load_variant(r#"{"cages": [{"cells": ["r1c1", "r9c10"], "sum": 5}]}"#).err(),
+        Some(VariantError::Cages(CageError::CellOutOfRange {
+            row: 8,
+            col: 9
+        }))
+    );
+    assert_eq!(
+        load_variant(r#"{"regions": "aab abb ccc", "extra_units": ["windows"]}"#).err(),
+        Some(VariantError::NeedsBoxes {
+            name: "windows".to_string()
+        })
+    );
+    for (description, size) in [
+        (r#"{"box": [6, 6]}"#, 36),
+        (r#"{"size": 36}"#, 36),
+        (r#"{"size": 0}"#, 0),
+        (r#"{"box": [0, 3]}"#, 0),
+        (r#"{"size": 9, "box": [3, 0]}"#, 0),
+    ] {
+        assert_eq!(
+            load_variant(description).err(),
+            Some(VariantError::BadSize { size })
+        );
+    }
+    assert!(matches!(load_variant("{"), Err(VariantError::Json(_))));
+    assert!(matches!(
+        load_variant(&"[".repeat(200000)),
+        Err(VariantError::Json(JsonError::TooDeep { .. }))
+    ));
+}